@@ -1,10 +1,15 @@
 use crate::context::Context;
-use crate::output::{self, Output};
-use crate::rule::{ActionType, EventType, LogFormat, MatchResult, OnErrorBehavior};
+use crate::output::{self, Output, ResponseFields};
+use crate::rule::{
+    ActionType, BlockOutputMode, EvaluationResult, EventType, LogFormat, MatchResult,
+    OnErrorBehavior,
+};
+use crate::style;
+use crate::watchdog;
 use chrono::Local;
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 /// Resolves the working directory from the template or falls back to the context's file_dir.
 fn resolve_working_dir(working_dir: Option<&String>, context: &Context) -> Option<String> {
@@ -38,13 +43,245 @@ fn resolve_working_dir(working_dir: Option<&String>, context: &Context) -> Optio
     }
 }
 
+/// Builds the block message from the rule's message, suggested commands, and docs URL.
+///
+/// The description follows the message on its own line when
+/// `show_description` is set. Each suggestion is appended on its own line as
+/// `Suggested command: <cmd>`, followed by `See: <url>` when a docs URL is
+/// configured.
+fn block_message(match_result: &MatchResult, context: &Context) -> Option<String> {
+    let mut lines: Vec<String> = Vec::new();
+    if let Some(ref message) = match_result.message {
+        lines.push(context.expand(message));
+    }
+    if match_result.show_description
+        && let Some(ref description) = match_result.description
+    {
+        lines.push(description.clone());
+    }
+    for suggestion in &match_result.suggest {
+        lines.push(format!("Suggested command: {}", context.expand(suggestion)));
+    }
+    if let Some(ref docs_url) = match_result.docs_url {
+        lines.push(format!("See: {}", context.expand(docs_url)));
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// Expands a log file template, resolving `~` to the home directory and
+/// relative paths against the project the config was found in.
+fn resolve_log_path(file_path: &str, context: &Context) -> String {
+    let file_path = context.expand(file_path);
+    let expanded_path = if file_path.starts_with('~') {
+        if let Ok(home) = std::env::var("HOME") {
+            file_path.replacen('~', &home, 1)
+        } else {
+            file_path
+        }
+    } else {
+        file_path
+    };
+    // 親ディレクトリで見つかった設定の相対パスはそのプロジェクト基準で解決する
+    match &context.project_dir {
+        Some(project_dir) if std::path::Path::new(&expanded_path).is_relative() => project_dir
+            .join(&expanded_path)
+            .to_string_lossy()
+            .into_owned(),
+        _ => expanded_path,
+    }
+}
+
+/// Returns the top-level JSON response fields of the match result, with the
+/// `system_message` template expanded.
+pub fn response_fields(match_result: &MatchResult, context: &Context) -> ResponseFields {
+    let context = &context.with_values(&match_result.values);
+    ResponseFields {
+        suppress_output: match_result.suppress_output,
+        system_message: match_result
+            .system_message
+            .as_ref()
+            .map(|template| context.expand(template)),
+    }
+}
+
+/// Describes what `execute_action` would do for the match result without
+/// doing it, as `key: value` lines with every template expanded.
+pub fn describe_action(match_result: &MatchResult, context: &Context) -> Vec<String> {
+    let context = &context.with_values(&match_result.values);
+    match match_result.action {
+        ActionType::Block if match_result.quiet => {
+            vec!["message: (none, quiet = true)".to_string()]
+        }
+        ActionType::Block => match block_message(match_result, context) {
+            Some(message) => vec![format!("message: {message}")],
+            None => vec!["message: (none)".to_string()],
+        },
+        ActionType::Run => {
+            let command = match &match_result.run_command {
+                Some(template) => context.expand(template),
+                None => "(none)".to_string(),
+            };
+            let working_dir = resolve_working_dir(match_result.working_dir.as_ref(), context)
+                .unwrap_or_else(|| "(current directory)".to_string());
+            let on_error = match match_result.on_error {
+                OnErrorBehavior::Ignore => "ignore",
+                OnErrorBehavior::Fail => "fail",
+            };
+            vec![
+                format!("command: {command}"),
+                format!("working_dir: {working_dir}"),
+                format!("on_error: {on_error}"),
+            ]
+        }
+        ActionType::Log => {
+            let log_file = match &match_result.log_file {
+                Some(file_path) => resolve_log_path(file_path, context),
+                None => "(none)".to_string(),
+            };
+            let log_format = match match_result.log_format {
+                LogFormat::Text => "text",
+                LogFormat::Json => "json",
+            };
+            vec![
+                format!("log_file: {log_file}"),
+                format!("log_format: {log_format}"),
+            ]
+        }
+    }
+}
+
+/// Environment variable that turns cchooked off without editing settings.json.
+pub const DISABLE_ENV: &str = "CCHOOKED_DISABLE";
+
+/// Which actions are turned off by `CCHOOKED_DISABLE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisableMode {
+    /// Everything runs normally (unset, empty, or "0").
+    Enabled,
+    /// Block actions are skipped; run and log actions still execute ("block").
+    Block,
+    /// cchooked does nothing at all (any other value).
+    All,
+}
+
+impl DisableMode {
+    /// Reads the mode from `CCHOOKED_DISABLE`.
+    pub fn from_env() -> Self {
+        match std::env::var(DISABLE_ENV).unwrap_or_default().as_str() {
+            "" | "0" => DisableMode::Enabled,
+            "block" => DisableMode::Block,
+            _ => DisableMode::All,
+        }
+    }
+}
+
+/// Environment variable that turns every block decision into a warning.
+pub const REPORT_ONLY_ENV: &str = "CCHOOKED_REPORT_ONLY";
+
+/// Returns true if report-only mode is on (`CCHOOKED_REPORT_ONLY` set to
+/// anything but empty or "0").
+pub fn report_only_from_env() -> bool {
+    !matches!(
+        std::env::var(REPORT_ONLY_ENV).unwrap_or_default().as_str(),
+        "" | "0"
+    )
+}
+
+/// Downgrades a blocking hook output to a warning for report-only mode.
+///
+/// Whatever produced the block (a block action, a failing run action with
+/// `on_error = "fail"`, ...), the exit code becomes 0 and each line of the
+/// message is printed with a `[report-only]` prefix. Other outputs are
+/// returned unchanged.
+fn downgrade_to_warning(out: Output) -> Output {
+    if !out.blocked {
+        return out;
+    }
+    let warning = match out.stderr.as_deref() {
+        Some(message) => message
+            .lines()
+            .map(|line| format!("[report-only] would block: {line}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => "[report-only] would block".to_string(),
+    };
+    Output {
+        exit_code: 0,
+        stderr: Some(warning),
+        blocked: false,
+        ..out
+    }
+}
+
 /// Executes the action based on the match result.
 ///
 /// Processes the matched rule's action (Block, Run, or Log) and returns the appropriate output.
-pub fn execute_action(match_result: &MatchResult, context: &Context, event: &EventType) -> Output {
+/// Block actions are skipped (with a note on stderr) when `mode` is [`DisableMode::Block`].
+/// A message longer than the rule's `max_message_bytes` is truncated, and the
+/// rule's `exit_code`, if set, replaces the exit code of an action that ran.
+///
+/// With `report_only`, a block is downgraded to a warning. A block that
+/// remains is reported in the shape the rule's `block_output_mode` and the
+/// event call for: exit code 2 with the message on stderr, or a JSON
+/// decision on stdout with exit code 0.
+pub fn execute_action(
+    match_result: &MatchResult,
+    context: &Context,
+    event: &EventType,
+    mode: DisableMode,
+    report_only: bool,
+) -> Output {
+    let skipped = match_result.action == ActionType::Block && mode == DisableMode::Block;
+    let mut out = perform_action(match_result, context, event, mode);
+    if let Some(limit) = match_result.max_message_bytes {
+        out.stderr = out
+            .stderr
+            .map(|message| output::truncate_message(&message, limit));
+    }
+    if let Some(exit_code) = match_result.exit_code
+        && !skipped
+    {
+        out.exit_code = exit_code;
+        out.blocked = exit_code == 2;
+    }
+    if report_only {
+        out = downgrade_to_warning(out);
+    }
+    if out.blocked && match_result.block_output_mode == BlockOutputMode::Json {
+        let fields = response_fields(match_result, context);
+        let reason = out.stderr.as_deref();
+        out = match event {
+            EventType::PreToolUse => output::deny_output(reason, &fields),
+            EventType::PostToolUse => output::post_block_output(reason, &fields),
+        };
+    }
+    out
+}
+
+fn perform_action(
+    match_result: &MatchResult,
+    context: &Context,
+    event: &EventType,
+    mode: DisableMode,
+) -> Output {
+    let context = &context.with_values(&match_result.values);
     match match_result.action {
+        ActionType::Block if mode == DisableMode::Block => {
+            eprintln!(
+                "cchooked: rule '{}' did not block because block actions are disabled via {DISABLE_ENV}",
+                match_result.rule_name
+            );
+            output::no_match_output()
+        }
         ActionType::Block => {
-            let message = match_result.message.as_ref().map(|m| context.expand(m));
+            if match_result.quiet {
+                return output::block_output(None);
+            }
+            let message = block_message(match_result, context);
             output::block_output(message.as_deref())
         }
         ActionType::Run => {
@@ -72,7 +309,26 @@ pub fn execute_action(match_result: &MatchResult, context: &Context, event: &Eve
                     command.current_dir(dir);
                 }
 
-                let result = command.output();
+                #[cfg(unix)]
+                {
+                    use std::os::unix::process::CommandExt;
+                    // タイムアウト時に sh -c が起動した子孫ごと終了できるよう、
+                    // 独立したプロセスグループで実行する
+                    command.process_group(0);
+                }
+
+                let result = command
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .and_then(|child| {
+                        watchdog::track_child(Some(child.id()));
+                        let output = child.wait_with_output();
+                        watchdog::track_child(None);
+                        watchdog::wait_if_timed_out();
+                        output
+                    });
 
                 match result {
                     Ok(output_result) if output_result.status.success() => {
@@ -115,58 +371,261 @@ pub fn execute_action(match_result: &MatchResult, context: &Context, event: &Eve
                     )
                 }
                 LogFormat::Json => {
-                    let obj = serde_json::json!({
+                    let mut obj = serde_json::json!({
                         "timestamp": timestamp,
                         "event": event.as_str(),
                         "tool": &context.tool_name,
                         "command": &context.command,
                         "file_path": &context.file_path,
                     });
+                    if let Some(ref description) = match_result.description
+                        && let Some(map) = obj.as_object_mut()
+                    {
+                        map.insert("description".to_string(), description.clone().into());
+                    }
+                    if let Some(ref docs_url) = match_result.docs_url
+                        && let Some(map) = obj.as_object_mut()
+                    {
+                        map.insert("docs_url".to_string(), context.expand(docs_url).into());
+                    }
+                    if !context.bypassed.is_empty()
+                        && let Some(map) = obj.as_object_mut()
+                    {
+                        let bypassed: Vec<serde_json::Value> = context
+                            .bypassed
+                            .iter()
+                            .map(|(rule, env)| serde_json::json!({ "rule": rule, "env": env }))
+                            .collect();
+                        map.insert("bypassed".to_string(), bypassed.into());
+                    }
                     serde_json::to_string(&obj).unwrap_or_default()
                 }
             };
 
             if let Some(ref file_path) = match_result.log_file {
-                let expanded_path = if file_path.starts_with('~') {
-                    if let Ok(home) = std::env::var("HOME") {
-                        file_path.replacen('~', &home, 1)
-                    } else {
-                        file_path.clone()
-                    }
-                } else {
-                    file_path.clone()
-                };
+                append_log(file_path, context, &log_entry);
+            }
 
-                if let Some(parent) = std::path::Path::new(&expanded_path).parent()
-                    && let Err(e) = std::fs::create_dir_all(parent)
-                {
-                    eprintln!("Warning: failed to create log directory: {e}");
-                }
+            output::no_match_output()
+        }
+    }
+}
 
-                match OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(&expanded_path)
-                {
-                    Ok(mut file) => {
-                        if let Err(e) = writeln!(file, "{log_entry}") {
-                            eprintln!("Warning: failed to write log entry: {e}");
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Warning: failed to open log file '{}': {e}", expanded_path);
-                    }
-                }
+/// Appends a line to a log file, creating it and its directory as needed.
+/// Failures only produce a warning.
+fn append_log(file_path: &str, context: &Context, log_entry: &str) {
+    let expanded_path = resolve_log_path(file_path, context);
+
+    if let Some(parent) = std::path::Path::new(&expanded_path).parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        eprintln!(
+            "{}",
+            style::warning(&format!("Warning: failed to create log directory: {e}"))
+        );
+    }
+
+    match OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&expanded_path)
+    {
+        Ok(mut file) => {
+            if let Err(e) = writeln!(file, "{log_entry}") {
+                eprintln!(
+                    "{}",
+                    style::warning(&format!("Warning: failed to write log entry: {e}"))
+                );
             }
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                style::warning(&format!(
+                    "Warning: failed to open log file '{expanded_path}': {e}"
+                ))
+            );
+        }
+    }
+}
 
-            output::no_match_output()
+/// Records the match of a `dry_run` rule instead of executing its action.
+///
+/// The line `[cchooked dry-run] rule <name> would block: <message>` (or
+/// `would run: ...`, `would log to ...`) is appended to the rule's log_file
+/// in its log_format if one is set, and printed to stderr otherwise.
+pub fn record_dry_run(match_result: &MatchResult, context: &Context, event: &EventType) {
+    let context = &context.with_values(&match_result.values);
+    let (text, fields) = action_details(match_result, context);
+    let line = format!("[cchooked dry-run] rule {} {text}", match_result.rule_name);
+    let Some(ref file_path) = match_result.log_file else {
+        eprintln!("{line}");
+        return;
+    };
+
+    let timestamp = Local::now().format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+    let log_entry = match match_result.log_format {
+        LogFormat::Text => format!("[{timestamp}] {line}"),
+        LogFormat::Json => {
+            let mut obj = serde_json::json!({
+                "timestamp": timestamp,
+                "event": event.as_str(),
+                "tool": &context.tool_name,
+                "command": &context.command,
+                "file_path": &context.file_path,
+                "dry_run": true,
+            });
+            if let (Some(map), Some(fields)) = (obj.as_object_mut(), fields.as_object()) {
+                map.extend(fields.clone());
+            }
+            obj.to_string()
+        }
+    };
+    append_log(file_path, context, &log_entry);
+}
+
+/// Builds the output of a `--dry-run` invocation.
+///
+/// Block actions are decided as usual, so the exit code and block message
+/// match a real run, including a JSON decision, which follows the lines.
+/// Run and log actions are not executed; each matched action is reported
+/// on its own stdout line instead, as text or (with `json`) as a JSON
+/// object. Matches of `dry_run` rules are reported too, marked as shadow
+/// matches, but are not recorded.
+pub fn dry_run_output(
+    result: Option<&EvaluationResult>,
+    event: &EventType,
+    mode: DisableMode,
+    report_only: bool,
+    json: bool,
+) -> Output {
+    let Some(result) = result else {
+        let line = if json {
+            serde_json::json!({ "dry_run": true, "rule": null, "action": null }).to_string()
+        } else {
+            "dry-run: no rule matched".to_string()
+        };
+        return Output {
+            stdout: Some(line + "\n"),
+            ..output::no_match_output()
+        };
+    };
+
+    let mut lines = String::new();
+    for shadow in &result.shadow_results {
+        lines.push_str(&dry_run_line(shadow, &result.context, json, true));
+        lines.push('\n');
+    }
+    let mut out = output::no_match_output();
+    for matched in result.log_results.iter().chain(&result.terminal_result) {
+        lines.push_str(&dry_run_line(matched, &result.context, json, false));
+        lines.push('\n');
+        if matched.action == ActionType::Block {
+            out = execute_action(matched, &result.context, event, mode, report_only);
+        }
+    }
+    if let Some(decision) = out.stdout.take() {
+        lines.push_str(&decision);
+    }
+    out.stdout = Some(lines);
+    out
+}
+
+/// Describes what a matched action would have done, on a single line.
+/// `shadow` marks the match of a `dry_run` rule, which is never acted on.
+fn dry_run_line(match_result: &MatchResult, context: &Context, json: bool, shadow: bool) -> String {
+    let (text, fields) = action_details(match_result, context);
+    if !json {
+        let note = if shadow {
+            " (dry_run rule, not applied)"
+        } else {
+            ""
+        };
+        return format!("dry-run: rule '{}' {text}{note}", match_result.rule_name);
+    }
+    let mut obj = serde_json::json!({ "dry_run": true });
+    if shadow && let Some(map) = obj.as_object_mut() {
+        map.insert("shadow".to_string(), true.into());
+    }
+    if let (Some(map), Some(fields)) = (obj.as_object_mut(), fields.as_object()) {
+        map.extend(fields.clone());
+    }
+    obj.to_string()
+}
+
+/// Describes a matched action as a JSON object with `rule`, `action`, and
+/// the expanded parameters of the action.
+pub fn action_json(match_result: &MatchResult, context: &Context) -> serde_json::Value {
+    action_details(match_result, context).1
+}
+
+/// Returns the `--dry-run` line describing a matched action, and the same
+/// information as a JSON object.
+fn action_details(match_result: &MatchResult, context: &Context) -> (String, serde_json::Value) {
+    let context = &context.with_values(&match_result.values);
+    let mut obj = serde_json::json!({
+        "rule": match_result.rule_name,
+        "action": match_result.action.as_str(),
+    });
+    let (text, fields) = match match_result.action {
+        ActionType::Block => {
+            let message = if match_result.quiet {
+                None
+            } else {
+                block_message(match_result, context)
+            };
+            let text = match &message {
+                Some(message) => format!("would block: {}", message.replace('\n', " ")),
+                None => "would block".to_string(),
+            };
+            (text, serde_json::json!({ "message": message }))
+        }
+        ActionType::Run => {
+            let command = match_result
+                .run_command
+                .as_ref()
+                .map(|template| context.expand(template));
+            let working_dir = resolve_working_dir(match_result.working_dir.as_ref(), context);
+            let text = format!(
+                "would run: {} (working_dir: {})",
+                command.as_deref().unwrap_or_default(),
+                working_dir.as_deref().unwrap_or("current directory")
+            );
+            (
+                text,
+                serde_json::json!({ "command": command, "working_dir": working_dir }),
+            )
+        }
+        ActionType::Log => {
+            let log_file = match_result
+                .log_file
+                .as_ref()
+                .map(|file_path| resolve_log_path(file_path, context));
+            let log_format = match match_result.log_format {
+                LogFormat::Text => "text",
+                LogFormat::Json => "json",
+            };
+            let text = format!(
+                "would log to {} ({log_format})",
+                log_file.as_deref().unwrap_or_default()
+            );
+            (
+                text,
+                serde_json::json!({ "log_file": log_file, "log_format": log_format }),
+            )
         }
+    };
+
+    if let (Some(map), Some(fields)) = (obj.as_object_mut(), fields.as_object()) {
+        map.extend(fields.clone());
     }
+    (text, obj)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rule::BlockOutputMode;
 
     fn make_context(
         file_dir: &str,
@@ -179,11 +638,101 @@ mod tests {
             file_path: file_path.to_string(),
             file_dir: file_dir.to_string(),
             tool_name: "Bash".to_string(),
-            branch: "main".to_string(),
+            branch: std::cell::OnceCell::from("main".to_string()),
             workspace_root: workspace_root.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn make_match_result(message: Option<&str>, suggest: &[&str]) -> MatchResult {
+        MatchResult {
+            rule_name: "test".to_string(),
+            action: ActionType::Block,
+            message: message.map(str::to_string),
+            suggest: suggest.iter().map(|s| s.to_string()).collect(),
+            description: None,
+            show_description: false,
+            docs_url: None,
+            quiet: false,
+            block_output_mode: BlockOutputMode::ExitCode,
+            suppress_output: false,
+            system_message: None,
+            exit_code: None,
+            max_message_bytes: None,
+            run_command: None,
+            on_error: OnErrorBehavior::Ignore,
+            log_file: None,
+            log_format: LogFormat::Text,
+            working_dir: None,
+            values: Default::default(),
         }
     }
 
+    #[test]
+    fn test_block_message_without_suggest() {
+        let ctx = make_context("", "/home/user/project", "npm install", "");
+        let result = make_match_result(Some("use bun: ${command}"), &[]);
+        assert_eq!(
+            block_message(&result, &ctx),
+            Some("use bun: npm install".to_string())
+        );
+    }
+
+    #[test]
+    fn test_block_message_with_suggestions() {
+        let ctx = make_context("", "/home/user/project", "npm install", "");
+        let result = make_match_result(
+            Some("use bun"),
+            &["bun install", "cd ${workspace_root} && bun install"],
+        );
+        assert_eq!(
+            block_message(&result, &ctx),
+            Some(
+                "use bun\nSuggested command: bun install\nSuggested command: cd /home/user/project && bun install"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_block_message_suggest_only() {
+        let ctx = make_context("", "", "npm install", "");
+        let result = make_match_result(None, &["bun install"]);
+        assert_eq!(
+            block_message(&result, &ctx),
+            Some("Suggested command: bun install".to_string())
+        );
+        assert_eq!(block_message(&make_match_result(None, &[]), &ctx), None);
+    }
+
+    #[test]
+    fn test_block_message_with_docs_url() {
+        let ctx = make_context("", "", "git push", "");
+        let mut result = make_match_result(Some("blocked"), &["git push --dry-run"]);
+        result.docs_url = Some("https://wiki.example.com/policy/${branch}".to_string());
+        assert_eq!(
+            block_message(&result, &ctx),
+            Some(
+                "blocked\nSuggested command: git push --dry-run\nSee: https://wiki.example.com/policy/main"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_block_message_with_description() {
+        let ctx = make_context("", "", "git push", "");
+        let mut result = make_match_result(Some("blocked"), &[]);
+        result.description = Some("Pushes go through the release bot".to_string());
+        assert_eq!(block_message(&result, &ctx), Some("blocked".to_string()));
+
+        result.show_description = true;
+        assert_eq!(
+            block_message(&result, &ctx),
+            Some("blocked\nPushes go through the release bot".to_string())
+        );
+    }
+
     #[test]
     fn test_resolve_working_dir_none_with_file_dir() {
         let ctx = make_context("/home/user/project/src", "/home/user/project", "", "");
@@ -294,4 +843,52 @@ mod tests {
         let result = resolve_working_dir(Some(&template), &ctx);
         assert_eq!(result, Some("/home/user/project/../other".to_string()));
     }
+
+    #[test]
+    fn test_execute_action_json_block_shape_follows_event() {
+        let ctx = make_context("", "", "cat .env", "");
+        let mut result = make_match_result(Some("blocked"), &[]);
+        result.block_output_mode = BlockOutputMode::Json;
+
+        let out = execute_action(
+            &result,
+            &ctx,
+            &EventType::PreToolUse,
+            DisableMode::Enabled,
+            false,
+        );
+        assert_eq!((out.exit_code, out.blocked), (0, true));
+        assert!(
+            out.stdout
+                .is_some_and(|stdout| stdout.contains("\"permissionDecisionReason\":\"blocked\""))
+        );
+
+        let out = execute_action(
+            &result,
+            &ctx,
+            &EventType::PostToolUse,
+            DisableMode::Enabled,
+            false,
+        );
+        assert_eq!((out.exit_code, out.blocked), (0, true));
+        assert_eq!(
+            out.stdout.as_deref(),
+            Some("{\"decision\":\"block\",\"reason\":\"blocked\"}\n")
+        );
+
+        // report-only では JSON にせず警告に格下げする
+        let out = execute_action(
+            &result,
+            &ctx,
+            &EventType::PostToolUse,
+            DisableMode::Enabled,
+            true,
+        );
+        assert_eq!((out.exit_code, out.blocked), (0, false));
+        assert_eq!(out.stdout, None);
+        assert_eq!(
+            out.stderr.as_deref(),
+            Some("[report-only] would block: blocked")
+        );
+    }
 }
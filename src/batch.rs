@@ -0,0 +1,105 @@
+use crate::action::{self, DisableMode};
+use crate::config::Config;
+use crate::output::Output;
+use crate::rule::{self, ActionType, EventType, HookInput, Rule};
+use crate::{build_context, parse_input};
+use serde_json::json;
+
+/// What the hook would decide for an input, without executing actions.
+pub struct Decision {
+    /// The deciding rule and its action: the terminal rule, or the first
+    /// log rule if evaluation ran to the end.
+    pub rule: Option<(String, ActionType)>,
+    /// Exit code the hook would return, assuming run actions succeed.
+    pub exit_code: i32,
+    /// Whether the hook would block the tool call, which the exit code does
+    /// not show when the block is reported as a JSON decision.
+    blocked: bool,
+}
+
+impl Decision {
+    /// Evaluates the rules for `input` the way the hook would.
+    pub fn evaluate(event: &EventType, input: &HookInput, config: &Config, rules: &[Rule]) -> Self {
+        let context = build_context(input, config);
+        let result = rule::evaluate_rules(rules, event, input, context);
+        let out = action::dry_run_output(result.as_ref(), event, DisableMode::Enabled, false, true);
+        let rule = result.as_ref().and_then(|result| {
+            result
+                .terminal_result
+                .as_ref()
+                .or_else(|| result.log_results.first())
+                .map(|matched| (matched.rule_name.clone(), matched.action.clone()))
+        });
+        Decision {
+            rule,
+            exit_code: out.exit_code,
+            blocked: out.blocked,
+        }
+    }
+
+    /// Returns true if the hook would block the tool call.
+    pub fn blocked(&self) -> bool {
+        self.blocked
+    }
+}
+
+/// Evaluates each line of `inputs` as a hook input and prints one JSON
+/// result per line: the zero-based `index` of the line, the deciding `rule`
+/// and its `action`, the `decision`, and the `exit_code` the hook would
+/// return. Lines that are not valid hook input get an `error` record
+/// instead, and blank lines are skipped.
+///
+/// No action is executed; like `--dry-run`, run actions are assumed to
+/// succeed. A summary is printed to stderr, and the exit code is 1 if any
+/// line was invalid.
+pub fn run_batch(event: &EventType, inputs: &str, config: &Config, rules: &[Rule]) -> Output {
+    let mut stdout = String::new();
+    let (mut total, mut matched, mut blocked, mut invalid) = (0, 0, 0, 0);
+
+    for (index, line) in inputs.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        total += 1;
+        let input = serde_json::from_str(line)
+            .map_err(|e| e.to_string())
+            .and_then(|value| parse_input(value).map_err(|e| e.to_string()));
+        let record = match input {
+            Ok(input) => {
+                let decision = Decision::evaluate(event, &input, config, rules);
+                if decision.rule.is_some() {
+                    matched += 1;
+                }
+                if decision.blocked() {
+                    blocked += 1;
+                }
+                let outcome = if decision.blocked() { "block" } else { "allow" };
+                let exit_code = decision.exit_code;
+                let (rule, action) = decision.rule.unzip();
+                json!({
+                    "index": index,
+                    "rule": rule,
+                    "action": action.map(|action| action.as_str()),
+                    "decision": outcome,
+                    "exit_code": exit_code,
+                })
+            }
+            Err(e) => {
+                invalid += 1;
+                json!({ "index": index, "error": format!("invalid input: {e}") })
+            }
+        };
+        stdout.push_str(&record.to_string());
+        stdout.push('\n');
+    }
+
+    Output {
+        exit_code: i32::from(invalid > 0),
+        stdout: Some(stdout),
+        stderr: Some(format!(
+            "{total} inputs: {matched} matched, {blocked} blocked, {} allowed, {invalid} invalid",
+            total - blocked - invalid
+        )),
+        blocked: false,
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Number of bytes inspected when sniffing an existing file.
+const SNIFF_BYTES: u64 = 8192;
+
+/// Fraction of suspicious characters above which text is considered binary.
+const MAX_SUSPICIOUS_RATIO: f64 = 0.3;
+
+/// Extensions treated as binary without reading the file.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "ico", "webp", "pdf", "zip", "gz", "tgz", "bz2", "xz",
+    "7z", "tar", "jar", "class", "so", "dylib", "dll", "exe", "o", "a", "wasm", "sqlite",
+    "sqlite3", "db", "woff", "woff2", "ttf", "otf",
+];
+
+/// Returns true if text content looks binary.
+///
+/// Content containing a NUL character, or in which replacement and control
+/// characters (other than common whitespace) make up a large share, is
+/// considered binary.
+pub fn is_binary_text(content: &str) -> bool {
+    if content.contains('\0') {
+        return true;
+    }
+    let total = content.chars().count();
+    if total == 0 {
+        return false;
+    }
+    let suspicious = content
+        .chars()
+        .filter(|&c| c == char::REPLACEMENT_CHARACTER || is_suspicious_control(c))
+        .count();
+    suspicious as f64 / total as f64 > MAX_SUSPICIOUS_RATIO
+}
+
+/// Returns true if the file at `path` looks binary.
+///
+/// Files with a known binary extension are reported without being read;
+/// otherwise only the first few KB are inspected. Missing or unreadable
+/// files are not binary.
+pub fn is_binary_file(path: &Path) -> bool {
+    if !path.is_file() {
+        return false;
+    }
+    let has_binary_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            BINARY_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext))
+        });
+    if has_binary_extension {
+        return true;
+    }
+
+    let mut buffer = Vec::new();
+    let read = File::open(path).and_then(|file| file.take(SNIFF_BYTES).read_to_end(&mut buffer));
+    if read.is_err() {
+        return false;
+    }
+    if buffer.contains(&0) {
+        return true;
+    }
+    is_binary_text(&String::from_utf8_lossy(&buffer))
+}
+
+fn is_suspicious_control(c: char) -> bool {
+    c.is_control() && !matches!(c, '\n' | '\r' | '\t' | '\u{0c}')
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_text_content_is_not_binary() {
+        assert!(!is_binary_text("fn main() {\n\tprintln!(\"hi\");\n}\n"));
+        assert!(!is_binary_text("日本語のテキスト"));
+        assert!(!is_binary_text(""));
+    }
+
+    #[test]
+    fn test_nul_in_content_is_binary() {
+        assert!(is_binary_text("PK\u{3}\u{4}\0\0data"));
+    }
+
+    #[test]
+    fn test_mostly_control_characters_is_binary() {
+        assert!(is_binary_text("\u{1}\u{2}\u{3}\u{fffd}\u{fffd}ab"));
+        assert!(!is_binary_text("plain text with one \u{1b} escape"));
+    }
+
+    #[test]
+    fn test_binary_file_detection() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let text = temp_dir.path().join("notes.txt");
+        fs::write(&text, "hello\n").unwrap();
+        assert!(!is_binary_file(&text));
+
+        let blob = temp_dir.path().join("data.bin");
+        fs::write(&blob, [0x7f, b'E', b'L', b'F', 0, 0, 1]).unwrap();
+        assert!(is_binary_file(&blob));
+
+        let invalid_utf8 = temp_dir.path().join("latin1.dat");
+        fs::write(&invalid_utf8, [0xff, 0xfe, 0xfd, 0xfc, b'a']).unwrap();
+        assert!(is_binary_file(&invalid_utf8));
+    }
+
+    #[test]
+    fn test_binary_extension_fallback() {
+        let temp_dir = TempDir::new().unwrap();
+        let image = temp_dir.path().join("logo.PNG");
+        fs::write(&image, "not really a png").unwrap();
+        assert!(is_binary_file(&image));
+    }
+
+    #[test]
+    fn test_missing_file_is_not_binary() {
+        assert!(!is_binary_file(Path::new(
+            "/nonexistent/cchooked/image.png"
+        )));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Cache file used for the default config location.
+const DEFAULT_CACHE_PATH: &str = ".claude/.hooks-rules.cache";
+
+/// Environment variable that reports cache hits and misses on stderr.
+const TRACE_ENV: &str = "CCHOOKED_TRACE_CACHE";
+
+/// On-disk form of a cached config (`C` is `&Config` when writing).
+#[derive(Deserialize, Serialize)]
+struct CacheFile<C> {
+    /// cchooked version that wrote the cache.
+    version: String,
+    /// The `--config` path the config was loaded for (None: default locations).
+    config_path: Option<String>,
+    /// State of every file the config depends on when it was cached.
+    stamps: Vec<FileStamp>,
+    config: C,
+    rule_order: Vec<String>,
+    rule_sources: HashMap<String, String>,
+    source_files: Vec<PathBuf>,
+    warnings: Vec<String>,
+}
+
+/// Size and modification time of a path (both None if it did not exist).
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct FileStamp {
+    path: PathBuf,
+    size: Option<u64>,
+    mtime: Option<(u64, u32)>,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Self {
+        let metadata = fs::metadata(path).ok();
+        let mtime = metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| (d.as_secs(), d.subsec_nanos()));
+        Self {
+            path: path.to_path_buf(),
+            size: metadata.map(|m| m.len()),
+            mtime,
+        }
+    }
+}
+
+/// Returns where the cache for a config path is stored.
+///
+/// An explicit config file is cached next to itself as `.<name>.cache`. The
+/// default location is cached in the project's `.claude` directory, only if
+/// it exists.
+fn cache_path(config_path: Option<&str>, project_dir: &Path) -> Option<PathBuf> {
+    match config_path {
+        Some(config_path) => {
+            let path = Path::new(config_path);
+            let name = path.file_name()?.to_string_lossy();
+            Some(path.with_file_name(format!(".{name}.cache")))
+        }
+        None => {
+            let path = project_dir.join(DEFAULT_CACHE_PATH);
+            path.parent().is_some_and(Path::is_dir).then_some(path)
+        }
+    }
+}
+
+fn stamps(watched: &[PathBuf], source_files: &[PathBuf]) -> Vec<FileStamp> {
+    let mut paths: Vec<&PathBuf> = watched.iter().chain(source_files).collect();
+    paths.sort();
+    paths.dedup();
+    paths.into_iter().map(|path| FileStamp::of(path)).collect()
+}
+
+fn trace(message: &str) {
+    if std::env::var_os(TRACE_ENV).is_some_and(|v| !v.is_empty()) {
+        eprintln!("cchooked: {message}");
+    }
+}
+
+/// Returns the cached config if it is still valid.
+///
+/// Any problem (missing, corrupt, or stale cache, or a different cchooked
+/// version) is a miss.
+pub fn read(config_path: Option<&str>, project_dir: &Path, watched: &[PathBuf]) -> Option<Config> {
+    let cached = cache_path(config_path, project_dir)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<CacheFile<Config>>(&bytes).ok())
+        .filter(|cached| {
+            cached.version == env!("CARGO_PKG_VERSION")
+                && cached.config_path.as_deref() == config_path
+                && cached.stamps == stamps(watched, &cached.source_files)
+        });
+    let Some(cached) = cached else {
+        trace("config cache miss");
+        return None;
+    };
+    trace("config cache hit");
+
+    let mut config = cached.config;
+    config.rule_order = cached.rule_order;
+    config.rule_sources = cached.rule_sources;
+    config.source_files = cached.source_files;
+    config.warnings = cached.warnings;
+    Some(config)
+}
+
+/// Writes the config to the cache. Failures are ignored; the next run simply
+/// parses the config again.
+pub fn write(config_path: Option<&str>, project_dir: &Path, watched: &[PathBuf], config: &Config) {
+    let Some(path) = cache_path(config_path, project_dir) else {
+        return;
+    };
+    let cached = CacheFile {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        config_path: config_path.map(str::to_string),
+        stamps: stamps(watched, &config.source_files),
+        config,
+        rule_order: config.rule_order.clone(),
+        rule_sources: config.rule_sources.clone(),
+        source_files: config.source_files.clone(),
+        warnings: config.warnings.clone(),
+    };
+    let Ok(json) = serde_json::to_string(&cached) else {
+        return;
+    };
+
+    // 途中で中断されても壊れたキャッシュが残らないよう一時ファイルからリネームする
+    let tmp_path = path.with_extension(format!("cache.{}.tmp", std::process::id()));
+    if fs::write(&tmp_path, json).is_ok() && fs::rename(&tmp_path, &path).is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::config::Config;
+use crate::error::CchookedError;
+use crate::output::Output;
+use crate::rule::{self, ActionType, Rule, ToolMatcher, WhenCondition};
+use regex_lite::Regex;
+
+/// Input fields provided by the built-in tools, by tool name.
+///
+/// Tools not listed here may provide any field, so rules matching them are
+/// never reported as unreachable.
+const TOOL_FIELDS: &[(&str, &[&str])] = &[
+    ("Bash", &["command", "exit code"]),
+    ("Write", &["file_path", "content"]),
+    ("Edit", &["file_path", "edits"]),
+    ("MultiEdit", &["file_path", "edits"]),
+    ("Read", &["file_path"]),
+    ("Task", &["subagent_type", "prompt"]),
+];
+
+/// Matcher patterns that match every tool name.
+const MATCH_ALL_PATTERNS: &[&str] = &["", ".*", "^.*", "^.*$", "^(?:.*)$"];
+
+/// Compiles every rule of the config and reports the result of each.
+///
+/// Warnings from loading the config come first as `WARN  config:` lines.
+/// Rules that compile are listed in evaluation order as `OK`, or with a
+/// `WARN` line per suspicious setting or reason they can never match; rules
+/// that fail to compile follow as `ERROR`, and disabled rules as `INFO`.
+/// The reachability analysis is conservative: a rule is only reported when
+/// it certainly cannot match. Exits with 1 if any rule fails to compile.
+pub fn run_check(config: &Config) -> Output {
+    let mut rules = Vec::new();
+    let mut errors = Vec::new();
+    for (name, result) in rule::compile_each(config) {
+        match result {
+            Ok(rule) => rules.push(rule),
+            Err(e) => errors.push((name, e)),
+        }
+    }
+    rule::sort_rules(&mut rules, config);
+
+    let ties = rule::priority_ties(&rules, config);
+    let mut report: Vec<String> = config
+        .warnings
+        .iter()
+        .map(|warning| format!("WARN  config: {warning}"))
+        .collect();
+    let mut warnings = config.warnings.len();
+    for (i, rule) in rules.iter().enumerate() {
+        let mut findings = rule.warnings.clone();
+        findings.extend(
+            ties.iter()
+                .filter(|(_, second)| second.name == rule.name)
+                .map(|(first, _)| {
+                    format!(
+                        "has the same priority, event and matcher as rule '{}', which is evaluated first",
+                        first.name
+                    )
+                }),
+        );
+        findings.extend(
+            rules
+                .iter()
+                .take(i)
+                .find(|earlier| shadows(earlier, rule))
+                .map(|earlier| {
+                    format!(
+                        "shadowed by rule '{}', which is evaluated first for the same event and tools and has no when conditions",
+                        earlier.name
+                    )
+                }),
+        );
+        findings.extend(unreachable_conditions(rule));
+
+        if findings.is_empty() {
+            report.push(format!("OK    {}", rule.name));
+        }
+        for finding in findings {
+            warnings += 1;
+            report.push(format!("WARN  {}: {finding}", rule.name));
+        }
+    }
+
+    for (name, error) in &errors {
+        report.push(format!(
+            "ERROR {name}: {}",
+            error.to_string().replace('\n', "\n      ")
+        ));
+    }
+
+    let mut disabled: Vec<&String> = config
+        .rules
+        .iter()
+        .filter(|(_, rule)| rule.enabled == Some(false) && rule.is_abstract != Some(true))
+        .map(|(name, _)| name)
+        .collect();
+    disabled.sort();
+    for name in disabled {
+        report.push(format!("INFO  {name}: disabled (enabled = false)"));
+    }
+
+    let plural = |count: usize| if count == 1 { "" } else { "s" };
+    let total = rules.len() + errors.len();
+    report.push(format!(
+        "{total} rule{}, {} error{}, {warnings} warning{}",
+        plural(total),
+        errors.len(),
+        plural(errors.len()),
+        plural(warnings)
+    ));
+    Output {
+        exit_code: if errors.is_empty() { 0 } else { 1 },
+        stdout: Some(report.join("\n") + "\n"),
+        stderr: None,
+        blocked: false,
+    }
+}
+
+/// Reports a config that could not be loaded at all.
+pub fn config_error(error: &CchookedError) -> Output {
+    Output {
+        exit_code: 1,
+        stdout: Some(format!("ERROR {error}\n")),
+        stderr: None,
+        blocked: false,
+    }
+}
+
+/// Returns true if `earlier` always stops evaluation before `rule` is reached.
+fn shadows(earlier: &Rule, rule: &Rule) -> bool {
+    earlier.event == rule.event
+        && matches!(earlier.action, ActionType::Block | ActionType::Run)
+        && earlier.unconditional
+        && !earlier.dry_run
+        && earlier.allow_if_env.is_none()
+        && (matches_every_tool(&earlier.matcher) || earlier.matcher.same_as(&rule.matcher))
+}
+
+fn matches_every_tool(matcher: &ToolMatcher) -> bool {
+    match matcher {
+        ToolMatcher::Any => true,
+        ToolMatcher::Regex(regex) => MATCH_ALL_PATTERNS.contains(&regex.as_str()),
+        ToolMatcher::Names { .. } => false,
+    }
+}
+
+/// Returns the tool names a matcher selects, or `None` if they cannot be
+/// listed (the matcher is a real regex or matches every tool).
+fn matched_tools(matcher: &ToolMatcher) -> Option<Vec<String>> {
+    match matcher {
+        ToolMatcher::Any => None,
+        ToolMatcher::Names { names, .. } => Some(names.clone()),
+        ToolMatcher::Regex(regex) => literal_names(regex.as_str()),
+    }
+}
+
+/// Extracts the names from a pattern that is a plain alternation of names,
+/// such as `Bash`, `^(Edit|Write)$`, or a `match_mode = "full"` wrapper.
+fn literal_names(pattern: &str) -> Option<Vec<String>> {
+    let mut pattern = pattern;
+    loop {
+        let stripped = pattern
+            .strip_prefix('^')
+            .and_then(|p| p.strip_suffix('$'))
+            .or_else(|| {
+                pattern
+                    .strip_prefix("(?:")
+                    .or_else(|| pattern.strip_prefix('('))
+                    .and_then(|p| p.strip_suffix(')'))
+            });
+        match stripped {
+            Some(inner) => pattern = inner,
+            None => break,
+        }
+    }
+    let names: Vec<String> = pattern.split('|').map(str::to_string).collect();
+    names
+        .iter()
+        .all(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .then_some(names)
+}
+
+/// Returns the input fields a rule's top-level conditions cannot do without,
+/// paired with the condition that needs each.
+///
+/// Regex conditions that match an empty string are skipped, since a missing
+/// field is matched as an empty string.
+fn required_fields(when: &WhenCondition) -> Vec<(&'static str, &'static str)> {
+    let needs_value = |patterns: &[Regex]| {
+        !patterns.is_empty() && !patterns.iter().any(|pattern| pattern.is_match(""))
+    };
+    let candidates = [
+        (
+            "when.command",
+            "command",
+            needs_value(&when.command_patterns),
+        ),
+        (
+            "when.command_all",
+            "command",
+            needs_value(&when.command_all_patterns),
+        ),
+        ("when.executable", "command", !when.executables.is_empty()),
+        ("when.parsed", "command", when.parsed.is_some()),
+        (
+            "when.file_path",
+            "file_path",
+            needs_value(&when.file_path_patterns),
+        ),
+        ("when.file_glob", "file_path", !when.file_globs.is_empty()),
+        (
+            "when.file_path_all",
+            "file_path",
+            needs_value(&when.file_path_all_patterns),
+        ),
+        ("when.content", "content", !when.content_patterns.is_empty()),
+        (
+            "when.min_content_bytes",
+            "content",
+            when.min_content_bytes.is_some_and(|min| min > 0),
+        ),
+        (
+            "when.added_text",
+            "edits",
+            !when.added_text_patterns.is_empty(),
+        ),
+        (
+            "when.removed_text",
+            "edits",
+            !when.removed_text_patterns.is_empty(),
+        ),
+        (
+            "when.subagent_type",
+            "subagent_type",
+            needs_value(&when.subagent_type_patterns),
+        ),
+        ("when.prompt", "prompt", needs_value(&when.prompt_patterns)),
+        (
+            "when.tool_exit_code",
+            "exit code",
+            when.tool_exit_code.is_some(),
+        ),
+    ];
+    candidates
+        .into_iter()
+        .filter(|(_, _, required)| *required)
+        .map(|(condition, field, _)| (condition, field))
+        .collect()
+}
+
+/// Describes the conditions of a rule that none of its matched tools can satisfy.
+fn unreachable_conditions(rule: &Rule) -> Vec<String> {
+    let Some(tools) = matched_tools(&rule.matcher) else {
+        return Vec::new();
+    };
+    let Some(provided) = tools
+        .iter()
+        .map(|tool| {
+            TOOL_FIELDS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(tool))
+                .map(|(_, fields)| *fields)
+        })
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Vec::new();
+    };
+
+    required_fields(&rule.when)
+        .into_iter()
+        .filter(|(_, field)| !provided.iter().any(|fields| fields.contains(field)))
+        .map(|(condition, field)| {
+            let verb = if tools.len() == 1 { "does" } else { "do" };
+            format!(
+                "{condition} can never match: {} {verb} not provide {field}",
+                tools.join(", ")
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_names() {
+        let names = |pattern: &str| literal_names(pattern);
+        assert_eq!(names("Bash"), Some(vec!["Bash".to_string()]));
+        assert_eq!(
+            names("^(Edit|Write)$"),
+            Some(vec!["Edit".to_string(), "Write".to_string()])
+        );
+        assert_eq!(names("^(?:Read)$"), Some(vec!["Read".to_string()]));
+        assert_eq!(names("Edit|.*"), None);
+        assert_eq!(names("(Edit)|(Write)"), None);
+        assert_eq!(names("mcp__.*"), None);
+    }
+}
@@ -0,0 +1,844 @@
+use crate::output::Output;
+
+/// What cchooked was asked to do, selected by the first non-option argument.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Command {
+    /// Evaluate the rules for a hook event (`cchooked <EVENT>`), or check
+    /// the rules' examples with `--check-examples`.
+    #[default]
+    Hook,
+    Test,
+    Check,
+    List,
+    Explain,
+    Eval,
+    Batch,
+    Replay,
+    Init,
+    Install,
+    MigrateFromSettings,
+    Completions,
+    /// Print the enabled rule names for shell completion (hidden).
+    CompleteRules,
+}
+
+/// Parsed command line.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub command: Command,
+    /// `<EVENT>` argument of hook mode, test and explain, or `<SHELL>` of
+    /// completions.
+    pub argument: Option<String>,
+    pub config_path: Option<String>,
+    pub only_tags: Option<String>,
+    pub skip_tags: Option<String>,
+    pub profile: Option<String>,
+    /// `--rule` names, in the order given.
+    pub rules: Vec<String>,
+    /// `--event` of list and eval.
+    pub event_option: Option<String>,
+    pub format: Option<String>,
+    pub report: Option<String>,
+    pub report_file: Option<String>,
+    pub fixtures: Option<String>,
+    pub expect_file: Option<String>,
+    pub settings_path: Option<String>,
+    pub audit_log: Option<String>,
+    pub install_events: Option<String>,
+    pub tool: Option<String>,
+    pub command_line: Option<String>,
+    pub file_path: Option<String>,
+    pub branch: Option<String>,
+    pub stdin_timeout_ms: Option<u64>,
+    pub check_examples: bool,
+    pub fail_on_change: bool,
+    pub force: bool,
+    pub local: bool,
+    pub write: bool,
+    pub dry_run: bool,
+    pub execute: bool,
+    pub verbose: bool,
+    pub show_help: bool,
+    pub show_version: bool,
+}
+
+impl Args {
+    /// Stores the value of the option named `long` (`None` for flags).
+    fn set(&mut self, long: &str, value: Option<String>) -> Result<(), String> {
+        match long {
+            "--stdin-timeout-ms" => {
+                let value = value.unwrap_or_default();
+                let timeout = value.parse().map_err(|_| {
+                    format!("invalid value '{value}' for '{long}': expected milliseconds")
+                })?;
+                self.stdin_timeout_ms = Some(timeout);
+            }
+            "--config" => self.config_path = value,
+            "--profile" => self.profile = value,
+            "--only-tags" => self.only_tags = value,
+            "--skip-tags" => self.skip_tags = value,
+            "--rule" => self.rules.extend(value),
+            "--event" => self.event_option = value,
+            "--format" => self.format = value,
+            "--report" => self.report = value,
+            "--report-file" => self.report_file = value,
+            "--fixtures" => self.fixtures = value,
+            "--expect-file" => self.expect_file = value,
+            "--settings" => self.settings_path = value,
+            "--audit-log" => self.audit_log = value,
+            "--events" => self.install_events = value,
+            "--tool" => self.tool = value,
+            "--command" => self.command_line = value,
+            "--file-path" => self.file_path = value,
+            "--branch" => self.branch = value,
+            "--check-examples" => self.check_examples = true,
+            "--fail-on-change" => self.fail_on_change = true,
+            "--force" => self.force = true,
+            "--local" => self.local = true,
+            "--write" => self.write = true,
+            "--dry-run" => self.dry_run = true,
+            "--execute" => self.execute = true,
+            "--verbose" => self.verbose = true,
+            "--help" => self.show_help = true,
+            "--version" => self.show_version = true,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// What shell completion offers for a value.
+#[derive(Clone, Copy)]
+pub enum Values {
+    /// Nothing in particular.
+    Any,
+    /// One of these words.
+    Choices(&'static [&'static str]),
+    /// A file path.
+    Files,
+    /// A directory path.
+    Dirs,
+    /// The name of an enabled rule.
+    Rules,
+}
+
+/// Event names accepted as `<EVENT>`.
+const EVENTS: &[&str] = &["PreToolUse", "PostToolUse"];
+
+/// An option accepted by a command.
+#[derive(Clone, Copy)]
+pub struct Opt {
+    pub long: &'static str,
+    pub short: Option<&'static str>,
+    /// Placeholder of the value (`None` for flags).
+    pub value: Option<&'static str>,
+    /// Help text; each line after the first is a continuation.
+    pub help: &'static str,
+    /// Values offered by shell completion.
+    pub values: Values,
+}
+
+impl Opt {
+    const fn flag(long: &'static str, help: &'static str) -> Self {
+        Opt {
+            long,
+            short: None,
+            value: None,
+            help,
+            values: Values::Any,
+        }
+    }
+
+    const fn value(long: &'static str, value: &'static str, help: &'static str) -> Self {
+        Opt {
+            long,
+            short: None,
+            value: Some(value),
+            help,
+            values: Values::Any,
+        }
+    }
+
+    const fn short(self, short: &'static str) -> Self {
+        Opt {
+            short: Some(short),
+            ..self
+        }
+    }
+
+    const fn completing(self, values: Values) -> Self {
+        Opt { values, ..self }
+    }
+
+    fn is(&self, name: &str) -> bool {
+        self.long == name || self.short == Some(name)
+    }
+}
+
+/// A positional argument accepted by a command.
+pub struct Argument {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub values: Values,
+}
+
+const EVENT: Argument = Argument {
+    name: "<EVENT>",
+    help: "Event type: PreToolUse or PostToolUse",
+    values: Values::Choices(EVENTS),
+};
+
+/// A command and the options it accepts.
+pub struct CommandSpec {
+    pub command: Command,
+    /// Subcommand name (empty for hook mode).
+    pub name: &'static str,
+    usage: &'static [&'static str],
+    /// Description; each line after the first is a continuation.
+    pub about: &'static str,
+    /// Positional argument, if one is accepted.
+    pub argument: Option<Argument>,
+    pub options: &'static [Opt],
+    /// Left out of the help and shell completion.
+    hidden: bool,
+}
+
+const CONFIG: Opt = Opt::value(
+    "--config",
+    "<PATH>",
+    "Path to config file (default: .claude/hooks-rules.toml)",
+)
+.completing(Values::Files);
+const PROFILE: Opt = Opt::value(
+    "--profile",
+    "<NAMES>",
+    "Activate these comma-separated profiles in order\n(default: $CCHOOKED_PROFILE)",
+);
+const ONLY_TAGS: Opt = Opt::value(
+    "--only-tags",
+    "<TAGS>",
+    "Only evaluate rules with one of these comma-separated tags\n(default: $CCHOOKED_ONLY_TAGS)",
+);
+const SKIP_TAGS: Opt = Opt::value(
+    "--skip-tags",
+    "<TAGS>",
+    "Skip rules with any of these comma-separated tags\n(default: $CCHOOKED_SKIP_TAGS)",
+);
+const RULE: Opt = Opt::value(
+    "--rule",
+    "<NAME>",
+    "Only evaluate this rule, even if another rule would\nmatch first (repeatable)",
+)
+.completing(Values::Rules);
+const STDIN_TIMEOUT: Opt = Opt::value(
+    "--stdin-timeout-ms",
+    "<MS>",
+    "Fail if stdin stays silent this long\n(default: 5000, 0 waits forever)",
+);
+const SETTINGS: Opt = Opt::value(
+    "--settings",
+    "<PATH>",
+    "Settings file to use (default: .claude/settings.json)",
+)
+.completing(Values::Files);
+const HELP: Opt = Opt::flag("--help", "Show this help message").short("-h");
+const VERSION: Opt = Opt::flag("--version", "Show version").short("-v");
+
+const HOOK: CommandSpec = CommandSpec {
+    command: Command::Hook,
+    name: "",
+    usage: &[
+        "cchooked <EVENT> [--dry-run [--format text|json]] [OPTIONS] < input.json",
+        "cchooked --check-examples [OPTIONS]",
+    ],
+    about: "Evaluate the rules for the hook input on stdin",
+    argument: Some(EVENT),
+    hidden: false,
+    options: &[
+        CONFIG,
+        PROFILE,
+        ONLY_TAGS,
+        SKIP_TAGS,
+        RULE,
+        STDIN_TIMEOUT,
+        Opt::flag(
+            "--dry-run",
+            "Report the matched actions on stdout instead of\nrunning run and log actions (the exit code is kept)",
+        ),
+        Opt::value(
+            "--format",
+            "<FORMAT>",
+            "Report format of --dry-run: text (default) or json",
+        )
+        .completing(Values::Choices(&["text", "json"])),
+        Opt::value(
+            "--report",
+            "<FORMAT>",
+            "Also write an evaluation report to stderr; the only\nformat is json",
+        )
+        .completing(Values::Choices(&["json"])),
+        Opt::value(
+            "--report-file",
+            "<PATH>",
+            "Append the report to this file instead of stderr\n(implies --report json)",
+        )
+        .completing(Values::Files),
+        Opt::flag(
+            "--verbose",
+            "Print the config files, each rule's outcome, the\nexpanded actions, and timings to stderr\n(also enabled by CCHOOKED_DEBUG=1)",
+        )
+        .short("-V"),
+        Opt::flag(
+            "--check-examples",
+            "Check each rule's examples against its patterns and exit",
+        ),
+    ],
+};
+
+const SUBCOMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        command: Command::Test,
+        name: "test",
+        usage: &[
+            "cchooked test [OPTIONS]",
+            "cchooked test <EVENT> --fixtures <DIR> [--expect-file <PATH>] [OPTIONS]",
+        ],
+        about: "Run the [[tests]] cases defined in the config, or replay the\nhook inputs in --fixtures (no actions are executed)",
+        argument: Some(EVENT),
+        hidden: false,
+        options: &[
+            CONFIG,
+            PROFILE,
+            ONLY_TAGS,
+            SKIP_TAGS,
+            Opt::value(
+                "--fixtures",
+                "<DIR>",
+                "Replay every *.json hook input in this directory",
+            )
+            .completing(Values::Dirs),
+            Opt::value(
+                "--expect-file",
+                "<PATH>",
+                "TOML file mapping fixture names to expected\noutcomes (block, allow, or rule:<name>)",
+            )
+            .completing(Values::Files),
+        ],
+    },
+    CommandSpec {
+        command: Command::Check,
+        name: "check",
+        usage: &["cchooked check [OPTIONS]"],
+        about: "Compile every rule and report errors, warnings, and rules\nthat can never match (exit 1 if any rule fails to compile)",
+        argument: None,
+        hidden: false,
+        options: &[CONFIG, PROFILE],
+    },
+    CommandSpec {
+        command: Command::List,
+        name: "list",
+        usage: &["cchooked list [--event <EVENT>] [--format table|json] [OPTIONS]"],
+        about: "Show the active rules in evaluation order, then disabled rules",
+        argument: None,
+        hidden: false,
+        options: &[
+            CONFIG,
+            PROFILE,
+            ONLY_TAGS,
+            SKIP_TAGS,
+            Opt::value("--event", "<EVENT>", "Only show rules for this event")
+                .completing(Values::Choices(EVENTS)),
+            Opt::value(
+                "--format",
+                "<FORMAT>",
+                "Output format: table (default) or json",
+            )
+            .completing(Values::Choices(&["table", "json"])),
+        ],
+    },
+    CommandSpec {
+        command: Command::Explain,
+        name: "explain",
+        usage: &["cchooked explain <EVENT> [OPTIONS] < input.json"],
+        about: "Show why each rule did or did not match the input on stdin and\nthe actions that would run (nothing is executed)",
+        argument: Some(EVENT),
+        hidden: false,
+        options: &[CONFIG, PROFILE, ONLY_TAGS, SKIP_TAGS, RULE, STDIN_TIMEOUT],
+    },
+    CommandSpec {
+        command: Command::Replay,
+        name: "replay",
+        usage: &["cchooked replay --audit-log <PATH> [--fail-on-change] [OPTIONS]"],
+        about: "Re-evaluate the inputs recorded by --report-file against the\ncurrent config and show the decisions that changed",
+        argument: None,
+        hidden: false,
+        options: &[
+            CONFIG,
+            PROFILE,
+            ONLY_TAGS,
+            SKIP_TAGS,
+            Opt::value(
+                "--audit-log",
+                "<PATH>",
+                "Report file written by --report-file (required)",
+            )
+            .completing(Values::Files),
+            Opt::flag(
+                "--fail-on-change",
+                "Exit with 1 if any decision changed",
+            ),
+        ],
+    },
+    CommandSpec {
+        command: Command::Eval,
+        name: "eval",
+        usage: &[
+            "cchooked eval --event <EVENT> [--tool <TOOL>] [--command <CMD>]\n              [--file-path <PATH>] [--branch <BRANCH>] [--execute] [OPTIONS]",
+        ],
+        about: "Evaluate the rules for an input given as options and show the\ndecision and exit code (run and log actions need --execute)",
+        argument: None,
+        hidden: false,
+        options: &[
+            CONFIG,
+            PROFILE,
+            ONLY_TAGS,
+            SKIP_TAGS,
+            RULE,
+            Opt::value("--event", "<EVENT>", "Event to evaluate (required)")
+                .completing(Values::Choices(EVENTS)),
+            Opt::value("--tool", "<TOOL>", "Tool name (default: Bash)"),
+            Opt::value("--command", "<CMD>", "tool_input.command"),
+            Opt::value("--file-path", "<PATH>", "tool_input.file_path")
+                .completing(Values::Files),
+            Opt::value(
+                "--branch",
+                "<BRANCH>",
+                "Git branch to assume (like $CCHOOKED_BRANCH)",
+            ),
+            Opt::flag("--execute", "Also execute run and log actions"),
+        ],
+    },
+    CommandSpec {
+        command: Command::Batch,
+        name: "batch",
+        usage: &["cchooked batch <EVENT> [OPTIONS] < inputs.ndjson"],
+        about: "Evaluate one hook input per line of stdin and print one JSON\nresult per line (no actions are executed)",
+        argument: Some(EVENT),
+        hidden: false,
+        options: &[CONFIG, PROFILE, ONLY_TAGS, SKIP_TAGS, RULE, STDIN_TIMEOUT],
+    },
+    CommandSpec {
+        command: Command::Init,
+        name: "init",
+        usage: &["cchooked init [--force] [--config <PATH>]"],
+        about: "Create a starter .claude/hooks-rules.toml and print the\nsettings.json hooks needed to run cchooked",
+        argument: None,
+        hidden: false,
+        options: &[
+            Opt::value(
+                "--config",
+                "<PATH>",
+                "Config file to create (default: .claude/hooks-rules.toml)",
+            )
+            .completing(Values::Files),
+            Opt::flag("--force", "Overwrite an existing config file"),
+        ],
+    },
+    CommandSpec {
+        command: Command::Install,
+        name: "install",
+        usage: &["cchooked install [--settings <PATH>] [--events <EVENTS>] [--local] [--dry-run]"],
+        about: "Add hooks running cchooked to .claude/settings.json (existing\ncchooked hooks are updated, not duplicated)",
+        argument: None,
+        hidden: false,
+        options: &[
+            SETTINGS,
+            Opt::value(
+                "--events",
+                "<EVENTS>",
+                "Comma-separated events to hook\n(default: PreToolUse,PostToolUse)",
+            )
+            .completing(Values::Choices(EVENTS)),
+            Opt::flag("--local", "Edit .claude/settings.local.json instead"),
+            Opt::flag("--dry-run", "Print the change as a diff without writing"),
+        ],
+    },
+    CommandSpec {
+        command: Command::MigrateFromSettings,
+        name: "migrate-from-settings",
+        usage: &["cchooked migrate-from-settings [--settings <PATH>] [--write] [--config <PATH>]"],
+        about: "Convert simple grep-block and logging hooks in settings.json\ninto rules, and list the hooks that need manual migration",
+        argument: None,
+        hidden: false,
+        options: &[
+            SETTINGS,
+            Opt::value(
+                "--config",
+                "<PATH>",
+                "Config file --write appends to\n(default: .claude/hooks-rules.toml)",
+            )
+            .completing(Values::Files),
+            Opt::flag(
+                "--write",
+                "Append the rules to the config file instead of\nprinting them",
+            ),
+        ],
+    },
+    CommandSpec {
+        command: Command::Completions,
+        name: "completions",
+        usage: &["cchooked completions <bash|zsh|fish>"],
+        about: "Print a shell completion script",
+        argument: Some(Argument {
+            name: "<SHELL>",
+            help: "Shell to complete for: bash, zsh, or fish",
+            values: Values::Choices(&["bash", "zsh", "fish"]),
+        }),
+        hidden: false,
+        options: &[],
+    },
+    CommandSpec {
+        command: Command::CompleteRules,
+        name: "__complete-rules",
+        usage: &["cchooked __complete-rules [OPTIONS]"],
+        about: "Print the names of the enabled rules, one per line",
+        argument: None,
+        hidden: true,
+        options: &[CONFIG, PROFILE],
+    },
+];
+
+/// A command line that could not be parsed.
+#[derive(Debug)]
+pub struct UsageError {
+    command: Command,
+    message: String,
+}
+
+impl UsageError {
+    /// Converts the error into output showing the message and the usage of
+    /// the command, exiting with 2.
+    pub fn into_output(self) -> Output {
+        let spec = spec(self.command);
+        let command = if spec.name.is_empty() {
+            "cchooked".to_string()
+        } else {
+            format!("cchooked {}", spec.name)
+        };
+        Output {
+            exit_code: 2,
+            stdout: None,
+            stderr: Some(format!(
+                "Error: {}\n\nUSAGE:\n{}\nRun '{command} --help' for more information.",
+                self.message,
+                usage_lines(spec)
+            )),
+            blocked: false,
+        }
+    }
+}
+
+/// Returns hook mode followed by every subcommand shown in the help.
+pub fn commands() -> impl Iterator<Item = &'static CommandSpec> {
+    std::iter::once(&HOOK).chain(SUBCOMMANDS.iter().filter(|spec| !spec.hidden))
+}
+
+impl CommandSpec {
+    /// Returns the options listed in the help of this command, including
+    /// `--help` (and `--version` in hook mode).
+    pub fn all_options(&'static self) -> impl Iterator<Item = &'static Opt> {
+        let version = (self.command == Command::Hook).then_some(&VERSION);
+        self.options.iter().chain([&HELP]).chain(version)
+    }
+}
+
+fn spec(command: Command) -> &'static CommandSpec {
+    SUBCOMMANDS
+        .iter()
+        .find(|spec| spec.command == command)
+        .unwrap_or(&HOOK)
+}
+
+/// Returns the option named `name` (long or short form) of `spec`.
+fn find_option(spec: &'static CommandSpec, name: &str) -> Option<&'static Opt> {
+    spec.options
+        .iter()
+        .chain([&HELP, &VERSION])
+        .find(|opt| opt.is(name))
+}
+
+/// Returns true if `arg` is an option that takes a separate value in any
+/// command.
+fn takes_value(arg: &str) -> bool {
+    std::iter::once(&HOOK)
+        .chain(SUBCOMMANDS)
+        .flat_map(|spec| spec.options)
+        .any(|opt| opt.is(arg) && opt.value.is_some())
+}
+
+fn is_option(arg: &str) -> bool {
+    arg.starts_with('-') && arg != "-"
+}
+
+/// Finds the command named by the first non-option argument. Options may
+/// come before it, as in `cchooked --config x.toml PreToolUse`.
+fn find_command(args: &[String]) -> &'static CommandSpec {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if is_option(arg) {
+            if !arg.contains('=') && takes_value(arg) {
+                iter.next();
+            }
+            continue;
+        }
+        return SUBCOMMANDS
+            .iter()
+            .find(|spec| spec.name == arg)
+            .unwrap_or(&HOOK);
+    }
+    &HOOK
+}
+
+/// Parses the command-line arguments (without the program name).
+///
+/// Options may appear before or after the command and take their value
+/// either as the next argument or after `=`. Any argument the command does
+/// not accept is an error.
+pub fn parse(args: &[String]) -> Result<Args, UsageError> {
+    let spec = find_command(args);
+    let error = |message: String| UsageError {
+        command: spec.command,
+        message,
+    };
+    let mut result = Args {
+        command: spec.command,
+        ..Args::default()
+    };
+    // フックモードでは最初の引数がイベント名になる
+    let mut command_seen = spec.command == Command::Hook;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !is_option(arg) {
+            if !command_seen {
+                command_seen = true;
+            } else if spec.argument.is_some() && result.argument.is_none() {
+                result.argument = Some(arg.clone());
+            } else {
+                return Err(error(format!("unexpected argument '{arg}'")));
+            }
+            continue;
+        }
+
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let Some(opt) = find_option(spec, name) else {
+            return Err(error(format!("unknown option '{name}'")));
+        };
+        let value = match (opt.value, inline_value) {
+            (None, None) => None,
+            (None, Some(_)) => {
+                return Err(error(format!(
+                    "option '{}' does not take a value",
+                    opt.long
+                )));
+            }
+            (Some(_), Some(value)) => Some(value),
+            (Some(placeholder), None) => match iter.next() {
+                Some(value) => Some(value.clone()),
+                None => {
+                    return Err(error(format!(
+                        "option '{}' requires a value {placeholder}",
+                        opt.long
+                    )));
+                }
+            },
+        };
+        result.set(opt.long, value).map_err(error)?;
+    }
+
+    Ok(result)
+}
+
+fn usage_lines(spec: &CommandSpec) -> String {
+    spec.usage
+        .iter()
+        .map(|usage| format!("    {}\n", usage.replace('\n', "\n    ")))
+        .collect()
+}
+
+/// Formats `name` in a column of `width` followed by `text`, whose
+/// continuation lines are aligned with its first line.
+fn column(name: &str, width: usize, text: &str) -> String {
+    let indent = " ".repeat(4 + width);
+    let mut out = if name.chars().count() < width {
+        format!("    {name:width$}")
+    } else {
+        format!("    {name}\n{indent}")
+    };
+    out.push_str(&text.replace('\n', &format!("\n{indent}")));
+    out.push('\n');
+    out
+}
+
+fn options_help<'a>(options: impl IntoIterator<Item = &'a Opt>) -> String {
+    options
+        .into_iter()
+        .map(|opt| {
+            let mut name = opt.long.to_string();
+            if let Some(short) = opt.short {
+                name = format!("{name}, {short}");
+            }
+            if let Some(value) = opt.value {
+                name = format!("{name} {value}");
+            }
+            column(&name, 19, opt.help)
+        })
+        .collect()
+}
+
+fn argument_help(argument: &Argument) -> String {
+    format!("ARGUMENTS:\n{}", column(argument.name, 11, argument.help))
+}
+
+/// Returns the help text of `command`.
+pub fn help(command: Command) -> String {
+    let spec = spec(command);
+    if spec.command == Command::Hook {
+        return global_help();
+    }
+
+    let mut out = format!(
+        "cchooked {}\n{}\n\nUSAGE:\n{}\n",
+        spec.name,
+        spec.about,
+        usage_lines(spec)
+    );
+    if let Some(argument) = &spec.argument {
+        out.push_str(&argument_help(argument));
+        out.push('\n');
+    }
+    out.push_str("OPTIONS:\n");
+    out.push_str(&options_help(spec.all_options()));
+    out
+}
+
+fn global_help() -> String {
+    let usage: String = commands().map(usage_lines).collect();
+    let commands: String = commands()
+        .skip(1)
+        .map(|spec| column(spec.name, 11, spec.about))
+        .collect();
+    let arguments = argument_help(&EVENT);
+    let options = options_help(HOOK.all_options());
+
+    format!(
+        r#"cchooked - Claude Code Hooks Engine
+
+USAGE:
+{usage}
+COMMANDS:
+{commands}
+Run 'cchooked <COMMAND> --help' for the options of a command.
+
+{arguments}
+OPTIONS:
+{options}
+ENVIRONMENT:
+    CCHOOKED_DISABLE   Set to 1 to turn cchooked off, or to "block" to skip
+                       block actions only (run and log actions still execute)
+    CCHOOKED_REPORT_ONLY
+                       Set to 1 to report blocks as warnings without enforcing
+                       them (exit 0)
+    CCHOOKED_DEBUG     Set to 1 to print diagnostics like --verbose
+    CCHOOKED_MAX_RUNTIME_MS
+                       Time limit of a hook run, overriding
+                       [settings] max_runtime_ms
+    NO_COLOR           Set to turn off colored stderr unless [settings]
+                       color = "always"
+
+EXAMPLES:
+    echo '{{"tool_name":"Bash","tool_input":{{"command":"npm install"}}}}' | cchooked PreToolUse
+    cchooked PreToolUse --config /path/to/hooks-rules.toml < input.json
+    cchooked PreToolUse --dry-run --format json < input.json
+    cchooked explain PreToolUse < input.json
+    cchooked eval --event PreToolUse --tool Bash --command "git push --force"
+"#
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn parse_strs(args: &[&str]) -> Result<Args, UsageError> {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        parse(&args)
+    }
+
+    #[test]
+    fn test_parse_legacy_hook_invocation() {
+        for args in [
+            &["PreToolUse", "--config", "x.toml"][..],
+            &["--config", "x.toml", "PreToolUse"],
+            &["PreToolUse", "--config=x.toml"],
+        ] {
+            let parsed = parse_strs(args).unwrap();
+            assert_eq!(parsed.command, Command::Hook);
+            assert_eq!(parsed.argument.as_deref(), Some("PreToolUse"));
+            assert_eq!(parsed.config_path.as_deref(), Some("x.toml"));
+        }
+    }
+
+    #[test]
+    fn test_parse_subcommand_with_event_and_options() {
+        let parsed = parse_strs(&[
+            "--profile",
+            "strict",
+            "test",
+            "PreToolUse",
+            "--fixtures",
+            "fx",
+        ])
+        .unwrap();
+        assert_eq!(parsed.command, Command::Test);
+        assert_eq!(parsed.argument.as_deref(), Some("PreToolUse"));
+        assert_eq!(parsed.profile.as_deref(), Some("strict"));
+        assert_eq!(parsed.fixtures.as_deref(), Some("fx"));
+
+        // オプションの値がサブコマンド名と同じでもよい
+        let parsed = parse_strs(&["eval", "--event", "PreToolUse", "--command", "list"]).unwrap();
+        assert_eq!(parsed.command, Command::Eval);
+        assert_eq!(parsed.command_line.as_deref(), Some("list"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let message = |args: &[&str]| parse_strs(args).unwrap_err().message;
+        assert_eq!(message(&["list", "--bogus"]), "unknown option '--bogus'");
+        assert_eq!(
+            message(&["list", "--fixtures", "x"]),
+            "unknown option '--fixtures'"
+        );
+        assert_eq!(
+            message(&["PreToolUse", "--config"]),
+            "option '--config' requires a value <PATH>"
+        );
+        assert_eq!(
+            message(&["init", "--force=yes"]),
+            "option '--force' does not take a value"
+        );
+        assert_eq!(message(&["check", "extra"]), "unexpected argument 'extra'");
+        assert_eq!(
+            message(&["PreToolUse", "--stdin-timeout-ms", "5s"]),
+            "invalid value '5s' for '--stdin-timeout-ms': expected milliseconds"
+        );
+        assert_eq!(
+            message(&["PreToolUse", "PostToolUse"]),
+            "unexpected argument 'PostToolUse'"
+        );
+    }
+}
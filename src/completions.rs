@@ -0,0 +1,282 @@
+use crate::cli::{self, CommandSpec, Opt, Values};
+use crate::output::Output;
+
+/// Command that prints the rule names offered for `--rule`.
+const COMPLETE_RULES: &str = "cchooked __complete-rules 2>/dev/null";
+
+/// Prints the completion script for `shell` on stdout.
+///
+/// The scripts are generated from the command specs in `cli`, so every
+/// subcommand and option accepted by the parser is completed.
+pub fn run_completions(shell: Option<&str>) -> Output {
+    let script = match shell {
+        Some("bash") => bash(),
+        Some("zsh") => zsh(),
+        Some("fish") => fish(),
+        other => {
+            let message = match other {
+                Some(shell) => format!("Unknown shell '{shell}'"),
+                None => "Missing shell argument".to_string(),
+            };
+            return Output {
+                exit_code: 2,
+                stdout: None,
+                stderr: Some(format!(
+                    "Error: {message}. Usage: cchooked completions <bash|zsh|fish>"
+                )),
+                blocked: false,
+            };
+        }
+    };
+    Output {
+        exit_code: 0,
+        stdout: Some(script),
+        stderr: None,
+        blocked: false,
+    }
+}
+
+/// Subcommand names, separated by spaces.
+fn subcommand_names() -> String {
+    cli::commands()
+        .skip(1)
+        .map(|spec| spec.name)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Words offered as the positional argument of `spec`. In hook mode these
+/// are the subcommands followed by the events.
+fn argument_words(spec: &CommandSpec) -> String {
+    let mut words = Vec::new();
+    if spec.name.is_empty() {
+        words.push(subcommand_names());
+    }
+    if let Some(Values::Choices(choices)) = spec.argument.as_ref().map(|arg| arg.values) {
+        words.push(choices.join(" "));
+    }
+    words.join(" ")
+}
+
+/// Option names of `spec` (long and short forms), separated by spaces.
+fn option_names(spec: &'static CommandSpec) -> String {
+    spec.all_options()
+        .flat_map(|opt| std::iter::once(opt.long).chain(opt.short))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Options of any command that take a value, as a `case` pattern.
+fn value_options_pattern() -> String {
+    let mut names: Vec<&str> = Vec::new();
+    for opt in cli::commands().flat_map(|spec| spec.all_options()) {
+        if opt.value.is_some() && !names.contains(&opt.long) {
+            names.extend(std::iter::once(opt.long).chain(opt.short));
+        }
+    }
+    names.join("|")
+}
+
+/// Options of `spec` that take a value.
+fn value_options(spec: &'static CommandSpec) -> impl Iterator<Item = &'static Opt> {
+    spec.all_options().filter(|opt| opt.value.is_some())
+}
+
+/// Pattern of the `case` branch of `spec` (`""` for hook mode).
+fn case_label(spec: &CommandSpec) -> String {
+    if spec.name.is_empty() {
+        "\"\"".to_string()
+    } else {
+        spec.name.to_string()
+    }
+}
+
+/// Scans the words before the cursor for the subcommand and its positional
+/// argument. Shared by bash and zsh; `first` is the index of the first
+/// argument and `end` the index of the word being completed.
+fn scan_words(words: &str, first: u32, end: &str) -> String {
+    format!(
+        r#"    local command="" argument="" i word
+    for ((i = {first}; i < {end}; i++)); do
+        word="${{{words}[i]}}"
+        case "$word" in
+            {value_options}) ((i++)) ;;
+            -*) ;;
+            *)
+                if [[ -z "$command" ]]; then
+                    command="$word"
+                elif [[ -z "$argument" ]]; then
+                    argument="$word"
+                fi
+                ;;
+        esac
+    done
+    case "$command" in
+        {subcommands}) ;;
+        *)
+            # hook mode: the first argument is the event
+            argument="$command"
+            command=""
+            ;;
+    esac
+"#,
+        value_options = value_options_pattern(),
+        subcommands = subcommand_names().replace(' ', "|"),
+    )
+}
+
+/// Generates the `case "$command"` block shared by bash and zsh. `values`
+/// completes the value of an option and `offer` offers a list of words.
+fn command_cases(values: fn(Values) -> String, offer: fn(&str) -> String) -> String {
+    let mut out = String::from("    case \"$command\" in\n");
+    for spec in cli::commands() {
+        out.push_str(&format!("        {})\n", case_label(spec)));
+        out.push_str("            case \"$prev\" in\n");
+        for opt in value_options(spec) {
+            let names: Vec<&str> = std::iter::once(opt.long).chain(opt.short).collect();
+            let reply = match values(opt.values) {
+                reply if reply.is_empty() => String::new(),
+                reply => format!(" {reply};"),
+            };
+            out.push_str(&format!(
+                "                {}){reply} return ;;\n",
+                names.join("|")
+            ));
+        }
+        out.push_str("            esac\n");
+        out.push_str(&format!(
+            "            if [[ \"$cur\" == -* ]]; then\n                {}\n",
+            offer(&option_names(spec))
+        ));
+        let words = argument_words(spec);
+        if !words.is_empty() {
+            out.push_str(&format!(
+                "            elif [[ -z \"$argument\" ]]; then\n                {}\n",
+                offer(&words)
+            ));
+        }
+        out.push_str("            fi\n            ;;\n");
+    }
+    out.push_str("    esac\n");
+    out
+}
+
+fn bash_offer(words: &str) -> String {
+    format!("COMPREPLY=($(compgen -W \"{words}\" -- \"$cur\"))")
+}
+
+fn bash_values(values: Values) -> String {
+    match values {
+        Values::Any => String::new(),
+        Values::Choices(choices) => bash_offer(&choices.join(" ")),
+        Values::Files => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+        Values::Dirs => "COMPREPLY=($(compgen -d -- \"$cur\"))".to_string(),
+        Values::Rules => bash_offer(&format!("$({COMPLETE_RULES})")),
+    }
+}
+
+fn bash() -> String {
+    let mut out = String::from(
+        "# bash completion for cchooked\n# Generated by `cchooked completions bash`\n\n_cchooked() {\n    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n    local prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n",
+    );
+    out.push_str(&scan_words("COMP_WORDS", 1, "COMP_CWORD"));
+    out.push_str(&command_cases(bash_values, bash_offer));
+    out.push_str("}\n\ncomplete -F _cchooked cchooked\n");
+    out
+}
+
+fn zsh_offer(words: &str) -> String {
+    format!("compadd -- {words}")
+}
+
+fn zsh_values(values: Values) -> String {
+    match values {
+        Values::Any => String::new(),
+        Values::Choices(choices) => zsh_offer(&choices.join(" ")),
+        Values::Files => "_files".to_string(),
+        Values::Dirs => "_files -/".to_string(),
+        Values::Rules => zsh_offer(&format!("${{(f)\"$({COMPLETE_RULES})\"}}")),
+    }
+}
+
+fn zsh() -> String {
+    let mut out = String::from(
+        "#compdef cchooked\n# zsh completion for cchooked\n# Generated by `cchooked completions zsh`\n\n_cchooked() {\n    local cur=\"${words[CURRENT]}\"\n    local prev=\"${words[CURRENT-1]}\"\n",
+    );
+    out.push_str(&scan_words("words", 2, "CURRENT"));
+    out.push_str(&command_cases(zsh_values, zsh_offer));
+    out.push_str(
+        "}\n\nif [[ \"$funcstack[1]\" == \"_cchooked\" ]]; then\n    _cchooked \"$@\"\nelse\n    compdef _cchooked cchooked\nfi\n",
+    );
+    out
+}
+
+/// Quotes `text` for fish, joining its lines into one.
+fn fish_quote(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish_values(values: Values) -> String {
+    match values {
+        Values::Any => String::new(),
+        Values::Choices(choices) => format!(" -a {}", fish_quote(&choices.join(" "))),
+        Values::Files => " -F".to_string(),
+        Values::Dirs => " -a '(__fish_complete_directories)'".to_string(),
+        Values::Rules => format!(" -a '({COMPLETE_RULES})'"),
+    }
+}
+
+fn fish() -> String {
+    let subcommands = subcommand_names();
+    let mut out = String::from(
+        "# fish completion for cchooked\n# Generated by `cchooked completions fish`\n\ncomplete -c cchooked -f\n",
+    );
+    for spec in cli::commands() {
+        let condition = if spec.name.is_empty() {
+            format!("'not __fish_seen_subcommand_from {subcommands}'")
+        } else {
+            format!("'__fish_seen_subcommand_from {}'", spec.name)
+        };
+        out.push('\n');
+        if spec.name.is_empty() {
+            for sub in cli::commands().skip(1) {
+                out.push_str(&format!(
+                    "complete -c cchooked -n __fish_use_subcommand -a {} -d {}\n",
+                    sub.name,
+                    fish_quote(sub.about)
+                ));
+            }
+        }
+        if let Some(argument) = &spec.argument
+            && let Values::Choices(choices) = argument.values
+        {
+            let condition = if spec.name.is_empty() {
+                "__fish_use_subcommand".to_string()
+            } else {
+                condition.clone()
+            };
+            out.push_str(&format!(
+                "complete -c cchooked -n {condition} -a {} -d {}\n",
+                fish_quote(&choices.join(" ")),
+                fish_quote(argument.help)
+            ));
+        }
+        for opt in spec.all_options() {
+            let mut line = format!(
+                "complete -c cchooked -n {condition} -l {}",
+                opt.long.trim_start_matches('-')
+            );
+            if let Some(short) = opt.short {
+                line.push_str(&format!(" -s {}", short.trim_start_matches('-')));
+            }
+            if opt.value.is_some() {
+                line.push_str(" -r");
+                line.push_str(&fish_values(opt.values));
+            }
+            line.push_str(&format!(" -d {}\n", fish_quote(opt.help)));
+            out.push_str(&line);
+        }
+    }
+    out
+}
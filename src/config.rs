@@ -1,74 +1,529 @@
+use crate::cache;
+use crate::context::BUILTIN_VARS;
 use crate::error::{CchookedError, Result};
-use serde::Deserialize;
+use crate::style;
+use crate::version::Version;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Default path for the hooks rules configuration file.
 pub const DEFAULT_CONFIG_PATH: &str = ".claude/hooks-rules.toml";
 
+/// Config schema version written by this cchooked (`schema = N`).
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// Default directory for drop-in configuration files.
+pub const DEFAULT_DROPIN_DIR: &str = ".claude/hooks-rules.d";
+
+/// Environment variable that disables the config cache when set.
+const NO_CACHE_ENV: &str = "CCHOOKED_NO_CACHE";
+
 /// Root configuration containing all hook rules.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Config {
+    /// Config format version (absent: 1).
+    pub schema: Option<i64>,
+    /// Oldest cchooked version that can use this config.
+    pub min_version: Option<String>,
+    /// Other config files to load and merge (relative to this file's directory).
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Skips merging the user-global config beneath this project config.
+    #[serde(default)]
+    pub ignore_global: bool,
+    /// Config-wide settings.
+    #[serde(default)]
+    pub settings: Settings,
+    /// User-defined template variables, expanded as `${name}`.
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
     /// Map of rule names to their configurations.
     #[serde(default)]
     pub rules: HashMap<String, RuleConfig>,
+    /// Rules in the ordered `[[rule]]` form (moved into `rules` after loading).
+    #[serde(default, rename = "rule")]
+    pub rule_list: Vec<OrderedRuleConfig>,
+    /// Named sets of rules loaded only when the profile is active.
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileConfig>,
+    /// Inline test cases checked by `cchooked test`.
+    #[serde(default)]
+    pub tests: Vec<ConfigTest>,
+    /// Names of `[[rule]]` rules in definition order, used to break priority ties.
+    #[serde(skip)]
+    pub rule_order: Vec<String>,
+    /// File each rule was loaded from, keyed by rule name.
+    #[serde(skip)]
+    pub rule_sources: HashMap<String, String>,
+    /// Every config file read to build this config.
+    #[serde(skip)]
+    pub source_files: Vec<PathBuf>,
+    /// Problems found while loading that do not stop it, reported by
+    /// `check` and `--verbose`.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+    /// Parent directory whose `.claude` holds the project config, if it was
+    /// found above the current directory.
+    #[serde(skip)]
+    pub project_dir: Option<PathBuf>,
 }
 
-fn default_on_error() -> String {
-    "ignore".to_string()
+/// An inline test case (`[[tests]]`).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConfigTest {
+    /// Name shown in the test report.
+    pub name: String,
+    /// Event type the input is evaluated for.
+    pub event: String,
+    /// Hook input as a table (`tool_name`, `tool_input`, ...).
+    #[serde(default)]
+    pub input: serde_json::Value,
+    /// Expected outcome: "block", "allow", "rule:<name>", or "transform:<command>".
+    pub expect: String,
 }
 
-fn default_log_format() -> String {
-    "text".to_string()
+/// A named profile (`[profiles.<name>]`).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    /// Rules added when the profile is active, overriding base rules of the same name.
+    #[serde(default)]
+    pub rules: HashMap<String, RuleConfig>,
+}
+
+/// Config-wide settings (`[settings]`).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Settings {
+    /// Ignores unknown fields instead of rejecting them (for configs shared
+    /// across cchooked versions).
+    #[serde(default)]
+    pub allow_unknown_fields: bool,
+    /// Appends rule descriptions to block messages.
+    #[serde(default)]
+    pub show_description: bool,
+    /// `on_error` for rules that don't set their own.
+    pub default_on_error: Option<String>,
+    /// `log_file` for rules that don't set their own.
+    pub default_log_file: Option<String>,
+    /// `log_format` for rules that don't set their own.
+    pub default_log_format: Option<String>,
+    /// `working_dir` for rules that don't set their own.
+    pub default_working_dir: Option<String>,
+    /// `block_output_mode` for rules that don't set their own.
+    pub default_block_output_mode: Option<String>,
+    /// Time limit of a hook invocation in milliseconds (0 or unset: none).
+    pub max_runtime_ms: Option<u64>,
+    /// Decision when `max_runtime_ms` is exceeded ("allow" or "block").
+    pub timeout_policy: Option<String>,
+    /// When stderr is colored ("auto", "always" or "never").
+    pub color: Option<String>,
+    /// Size limit of block messages in bytes, for rules that don't set
+    /// their own (unset: 4096, 0: none).
+    pub max_message_bytes: Option<u64>,
+    /// Whether errors that end a run are appended to the error log
+    /// (default true). Only applies once the config has loaded.
+    pub error_log: Option<bool>,
+}
+
+impl Settings {
+    /// Checks that the default values are valid.
+    fn validate(&self) -> std::result::Result<(), String> {
+        if let Some(ref on_error) = self.default_on_error
+            && !matches!(on_error.as_str(), "ignore" | "fail")
+        {
+            return Err(format!(
+                "[settings] default_on_error must be \"ignore\" or \"fail\", got \"{on_error}\""
+            ));
+        }
+        if let Some(ref log_format) = self.default_log_format
+            && !matches!(log_format.as_str(), "text" | "json")
+        {
+            return Err(format!(
+                "[settings] default_log_format must be \"text\" or \"json\", got \"{log_format}\""
+            ));
+        }
+        if let Some(ref mode) = self.default_block_output_mode
+            && !matches!(mode.as_str(), "exit_code" | "json")
+        {
+            return Err(format!(
+                "[settings] default_block_output_mode must be \"exit_code\" or \"json\", got \"{mode}\""
+            ));
+        }
+        if let Some(ref policy) = self.timeout_policy
+            && !matches!(policy.as_str(), "allow" | "block")
+        {
+            return Err(format!(
+                "[settings] timeout_policy must be \"allow\" or \"block\", got \"{policy}\""
+            ));
+        }
+        if let Some(ref color) = self.color
+            && !matches!(color.as_str(), "auto" | "always" | "never")
+        {
+            return Err(format!(
+                "[settings] color must be \"auto\", \"always\" or \"never\", got \"{color}\""
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Merges the rules of `other` into this config.
+    ///
+    /// Rules from `other` replace rules with the same name, with a warning
+    /// naming both files.
+    fn merge(&mut self, other: Config) {
+        self.warnings.extend(other.warnings);
+        for (name, rule) in other.rules {
+            let source = other.rule_sources.get(&name).cloned().unwrap_or_default();
+            if let Some(previous) = self.rule_sources.get(&name) {
+                self.warnings.push(format!(
+                    "rule '{name}' in {source} overrides the rule of the same name in {previous}"
+                ));
+            }
+            self.rule_order.retain(|ordered| *ordered != name);
+            self.rules.insert(name.clone(), rule);
+            self.rule_sources.insert(name, source);
+        }
+        self.rule_order.extend(other.rule_order);
+        self.source_files.extend(other.source_files);
+        self.tests.extend(other.tests);
+        self.merge_vars(other.vars);
+        for (profile_name, profile) in other.profiles {
+            let merged = self.profiles.entry(profile_name).or_default();
+            merged.rules.extend(profile.rules);
+        }
+    }
+
+    /// Adds variables that this config does not define itself.
+    fn merge_vars(&mut self, vars: HashMap<String, String>) {
+        for (name, value) in vars {
+            self.vars.entry(name).or_insert(value);
+        }
+    }
+
+    /// Merges the rules of `other` into this config, rejecting rule names
+    /// that are already defined.
+    fn merge_unique(&mut self, other: Config) -> Result<()> {
+        for name in other.rules.keys() {
+            if let Some(previous) = self.rule_sources.get(name) {
+                let source = other.rule_sources.get(name).cloned().unwrap_or_default();
+                return Err(CchookedError::ConfigParseError {
+                    path: source,
+                    detail: format!("rule '{name}' is already defined in {previous}"),
+                });
+            }
+        }
+        self.rules.extend(other.rules);
+        self.rule_sources.extend(other.rule_sources);
+        self.rule_order.extend(other.rule_order);
+        self.source_files.extend(other.source_files);
+        self.warnings.extend(other.warnings);
+        self.ignore_global |= other.ignore_global;
+        self.tests.extend(other.tests);
+        self.merge_vars(other.vars);
+        for (profile_name, profile) in other.profiles {
+            let merged = self.profiles.entry(profile_name).or_default();
+            merged.rules.extend(profile.rules);
+        }
+        Ok(())
+    }
+
+    /// Adds the rules of the named profiles, in order, on top of the base
+    /// rules. Unknown profile names produce a warning.
+    pub fn apply_profiles(&mut self, names: &[&str]) {
+        for name in names {
+            match self.profiles.remove(*name) {
+                Some(profile) => {
+                    for (rule_name, rule) in profile.rules {
+                        self.rule_sources
+                            .entry(rule_name.clone())
+                            .or_insert_with(|| format!("profile '{name}'"));
+                        self.rules.insert(rule_name, rule);
+                    }
+                }
+                None => eprintln!(
+                    "{}",
+                    style::warning(&format!("Warning: unknown profile '{name}'"))
+                ),
+            }
+        }
+    }
 }
 
 /// Configuration for a single hook rule.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct RuleConfig {
+    /// Name of the rule whose settings this rule inherits.
+    pub extends: Option<String>,
+    /// Marks a base rule that is only inherited from and never evaluated itself.
+    #[serde(rename = "abstract")]
+    pub is_abstract: Option<bool>,
     /// Event type (`PreToolUse` or `PostToolUse`).
-    pub event: String,
-    /// Regex pattern to match tool names.
-    pub matcher: String,
+    pub event: Option<String>,
+    /// Regex pattern to match tool names, or an array of exact tool names (absent: all tools).
+    pub matcher: Option<StringOrVec>,
+    /// MCP tool matcher over `mcp__<server>__<tool>` (exclusive with matcher).
+    pub matcher_mcp: Option<McpMatcherConfig>,
     /// Action to perform (block, run, or log).
-    pub action: String,
-    /// Priority for rule ordering (higher values are evaluated first).
-    #[serde(default)]
-    pub priority: i32,
+    pub action: Option<String>,
+    /// Whether the rule is evaluated (default true; disabled rules are not compiled).
+    pub enabled: Option<bool>,
+    /// Priority for rule ordering (default 0; higher values are evaluated first).
+    pub priority: Option<i32>,
+    /// Compiles the matcher and all when patterns case-insensitively.
+    pub ignore_case: Option<bool>,
+    /// Pattern anchoring: "search" (default, match anywhere) or "full" (match the entire string).
+    pub match_mode: Option<String>,
+    /// Compiles only the matcher case-insensitively (when patterns are unaffected).
+    pub matcher_ignore_case: Option<bool>,
+    /// Matches file path conditions against the path relative to workspace_root.
+    pub file_path_relative: Option<bool>,
+    /// Matches when.command against each compound command segment (default
+    /// true; false: whole string).
+    pub match_segments: Option<bool>,
+    /// Rejects unknown values for enumerated conditions such as
+    /// when.permission_mode (default true).
+    pub strict: Option<bool>,
     /// Optional message for block actions.
     pub message: Option<String>,
+    /// Alternative command(s) appended to the block message.
+    pub suggest: Option<StringOrVec>,
+    /// Human-readable explanation of the rule's intent.
+    pub description: Option<String>,
+    /// Labels used to select rules with --only-tags / --skip-tags.
+    pub tags: Option<Vec<String>>,
+    /// Documentation URL appended to block messages and json log entries.
+    pub docs_url: Option<String>,
+    /// Suppresses the block message on stderr (exit code is unchanged).
+    pub quiet: Option<bool>,
+    /// How blocks are reported ("exit_code" or "json"; default from settings).
+    pub block_output_mode: Option<String>,
+    /// Adds `"suppressOutput": true` to JSON output, hiding it from the transcript.
+    pub suppress_output: Option<bool>,
+    /// Notice shown to the user as the `systemMessage` of JSON output.
+    pub system_message: Option<String>,
+    /// Exit code that replaces the one produced by the rule's action (0-255).
+    pub exit_code: Option<i64>,
+    /// Size limit of the block message in bytes (0: none; default from settings).
+    pub max_message_bytes: Option<u64>,
+    /// Shadow mode: a match is only recorded and evaluation continues as if
+    /// the rule had not matched (default false).
+    pub dry_run: Option<bool>,
+    /// Environment variable that turns a block rule off while it is set.
+    pub allow_if_env: Option<AllowIfEnvConfig>,
     /// Optional conditional filters.
     #[serde(default)]
     pub when: Option<WhenConfig>,
+    /// Inputs the rule's patterns must or must not match (checked by --check-examples).
+    pub examples: Option<ExamplesConfig>,
     /// Command template for run actions.
     pub command: Option<String>,
     /// Working directory for run actions.
     pub working_dir: Option<String>,
-    /// Behavior when command fails (ignore or fail).
-    #[serde(default = "default_on_error")]
-    pub on_error: String,
+    /// Behavior when command fails (ignore or fail; default from settings).
+    pub on_error: Option<String>,
     /// File path for log actions.
     pub log_file: Option<String>,
-    /// Log format (text or json).
-    #[serde(default = "default_log_format")]
-    pub log_format: String,
+    /// Log format (text or json; default from settings).
+    pub log_format: Option<String>,
+}
+
+/// A rule in the ordered `[[rule]]` form.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OrderedRuleConfig {
+    /// Name identifier for the rule.
+    pub name: String,
+    /// The rule configuration.
+    #[serde(flatten)]
+    pub config: RuleConfig,
 }
 
 /// Conditional filter configuration for rule matching.
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct WhenConfig {
     /// Regex patterns to match against the command.
     pub command: Option<StringOrVec>,
+    /// Rules in Claude Code permission syntax (e.g. `Bash(git push:*)`).
+    pub permission_pattern: Option<StringOrVec>,
     /// Regex patterns to match against the file path.
     pub file_path: Option<StringOrVec>,
+    /// Glob patterns to match against the file path.
+    pub file_glob: Option<StringOrVec>,
     /// Branch names to match against.
     pub branch: Option<StringOrVec>,
+    /// Regex patterns to match against the origin remote URL.
+    pub remote: Option<StringOrVec>,
+    /// Regex patterns to match against the current login user name.
+    pub user: Option<StringOrVec>,
+    /// Regex patterns to match against the machine hostname.
+    pub hostname: Option<StringOrVec>,
     /// Executable names to match against (exact match on command name).
     pub executable: Option<StringOrVec>,
+    /// Operating systems on which the rule applies (linux, macos, windows).
+    pub os: Option<StringOrVec>,
+    /// Whether the rule applies only under CI (true) or only outside CI (false).
+    pub ci: Option<bool>,
+    /// Permission modes in which the rule applies (exact match).
+    pub permission_mode: Option<StringOrVec>,
+    /// Regex patterns to match against the model or agent identity.
+    pub model: Option<StringOrVec>,
+    /// Regex patterns to match against the tail of the session transcript.
+    pub transcript: Option<StringOrVec>,
+    /// Number of bytes read from the end of the transcript (default 256 KiB).
+    pub transcript_scan_bytes: Option<u64>,
+    /// Regex patterns to match against the (canonicalized) workspace root.
+    pub cwd: Option<StringOrVec>,
+    /// Whether file_path must lie outside (true) or inside (false) the workspace root.
+    pub outside_workspace: Option<bool>,
+    /// Whether the working tree must have (true) or not have (false) uncommitted changes.
+    pub git_dirty: Option<bool>,
+    /// Whether file_path must be ignored (true) or not ignored (false) by git.
+    pub file_in_gitignore: Option<bool>,
+    /// Whether file_path must be tracked (true) or not tracked (false) by git.
+    pub file_tracked: Option<bool>,
+    /// Whether the content or target file must be binary (true) or text (false).
+    pub binary: Option<bool>,
+    /// Exit code(s) of the Bash tool reported in PostToolUse tool_response.
+    pub tool_exit_code: Option<ExitCodeConfig>,
+    /// Regex patterns to match against the Write tool's content.
+    pub content: Option<StringOrVec>,
+    /// Minimum size in bytes of the written content (Write content or Edit replacements).
+    pub min_content_bytes: Option<usize>,
+    /// Maximum size in bytes of the written content (Write content or Edit replacements).
+    pub max_content_bytes: Option<usize>,
+    /// Regex patterns to match against the Task tool's subagent_type.
+    pub subagent_type: Option<StringOrVec>,
+    /// Regex patterns to match against the Task tool's prompt.
+    pub prompt: Option<StringOrVec>,
+    /// Regex patterns to match against lines added by an Edit/MultiEdit.
+    pub added_text: Option<StringOrVec>,
+    /// Regex patterns to match against lines removed by an Edit/MultiEdit.
+    pub removed_text: Option<StringOrVec>,
+    /// Paths of which at least one must exist (template-expanded).
+    pub path_exists: Option<StringOrVec>,
+    /// Paths of which none may exist (template-expanded).
+    pub path_missing: Option<StringOrVec>,
+    /// File names or globs of which at least one must exist in the workspace.
+    pub workspace_has: Option<StringOrVec>,
+    /// File names or globs of which none may exist in the workspace.
+    pub workspace_missing: Option<StringOrVec>,
+    /// Nested directory levels searched by workspace_has/workspace_missing (default 0).
+    pub workspace_depth: Option<usize>,
+    /// Environment variable names mapped to regex patterns their values must match.
+    pub env: Option<HashMap<String, StringOrVec>>,
+    /// Environment variables that must not be set.
+    pub env_unset: Option<StringOrVec>,
+    /// JSON pointers (RFC 6901) into tool_input mapped to regex patterns their values must match.
+    pub json: Option<HashMap<String, StringOrVec>>,
+    /// Regex patterns that must all match the command.
+    pub command_all: Option<StringOrVec>,
+    /// Regex patterns that must all match the file path.
+    pub file_path_all: Option<StringOrVec>,
+    /// Regex patterns that must all match the branch name.
+    pub branch_all: Option<StringOrVec>,
+    /// Time window during which the rule applies.
+    pub time: Option<TimeConfig>,
+    /// Structured program/argument matching on the parsed command.
+    pub parsed: Option<ParsedConfig>,
+    /// Negated conditions; a match of any of these disqualifies the rule.
+    pub not: Option<WhenNotConfig>,
+    /// Nested condition tables of which at least one must match.
+    pub any: Option<Vec<WhenConfig>>,
+    /// Nested condition tables that must all match.
+    pub all: Option<Vec<WhenConfig>>,
+}
+
+/// MCP tool matcher configuration (`matcher_mcp`).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct McpMatcherConfig {
+    /// Regex matched against the whole server name (any server if absent).
+    pub server: Option<String>,
+    /// Regex matched against the whole tool name (any tool if absent).
+    pub tool: Option<String>,
+}
+
+/// Example inputs for a rule's patterns (`examples`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ExamplesConfig {
+    /// Commands the rule's command conditions must match.
+    #[serde(rename = "match", default)]
+    pub matches: Vec<String>,
+    /// Commands the rule's command conditions must not match.
+    #[serde(default)]
+    pub no_match: Vec<String>,
+    /// File paths the rule's file path conditions must match.
+    #[serde(default)]
+    pub match_file_path: Vec<String>,
+    /// File paths the rule's file path conditions must not match.
+    #[serde(default)]
+    pub no_match_file_path: Vec<String>,
+}
+
+/// Structured command matching configuration (`when.parsed`).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ParsedConfig {
+    /// Regex patterns matched against the whole program name (argv[0] basename).
+    pub program: Option<StringOrVec>,
+    /// Regex patterns matched against the remaining arguments joined by spaces.
+    pub args: Option<StringOrVec>,
+}
+
+/// Time window configuration (`when.time`).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TimeConfig {
+    /// Start of the window ("HH:MM", inclusive).
+    pub after: Option<String>,
+    /// End of the window ("HH:MM", exclusive).
+    pub before: Option<String>,
+    /// Days of the week on which the rule applies (e.g. "mon").
+    pub days: Option<Vec<String>>,
+    /// Time zone used for evaluation ("local" or "utc").
+    pub tz: Option<String>,
+}
+
+/// Negated conditional filter configuration (`when.not`).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct WhenNotConfig {
+    /// Regex patterns that must not match the command.
+    pub command: Option<StringOrVec>,
+    /// Regex patterns that must not match the file path.
+    pub file_path: Option<StringOrVec>,
+    /// Regex patterns that must not match the current git branch.
+    pub branch: Option<StringOrVec>,
+}
+
+/// Bypass of a block rule (`allow_if_env`): a variable name, which bypasses
+/// the rule when set to any non-empty value, or a name and the exact value.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum AllowIfEnvConfig {
+    /// Bypasses the rule when the variable is non-empty.
+    Name(String),
+    /// Bypasses the rule when the variable equals `value`.
+    Exact {
+        /// Name of the environment variable.
+        name: String,
+        /// Value the variable must have.
+        value: String,
+    },
+}
+
+/// Exit code condition: an integer, a list of integers, or an expression such as "!=0".
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ExitCodeConfig {
+    /// A single exit code.
+    Code(i32),
+    /// Any of several exit codes.
+    Codes(Vec<i32>),
+    /// A comparison expression (e.g. "!=0", ">0", ">=2").
+    Expression(String),
 }
 
 /// A flexible type that accepts either a single string or an array of strings.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum StringOrVec {
     /// A single string value.
@@ -89,21 +544,638 @@ impl StringOrVec {
 
 /// Loads the configuration from a file.
 ///
-/// If no path is provided, uses the default configuration path.
+/// If no path is provided, uses the default configuration path of the
+/// nearest project (see [`find_project_dir`]) and also merges every
+/// `*.toml` file in the drop-in directory (sorted by filename).
+/// Either the main file or the drop-ins may be absent, but a rule name may
+/// only be defined once across them. The user-global config, if present, is
+/// merged beneath the project config unless it sets `ignore_global`.
+///
+/// An explicit path is loaded on its own. Files listed in `include` are
+/// loaded first (recursively), so rules defined in the including file
+/// override included rules of the same name.
+///
+/// Unless `CCHOOKED_NO_CACHE` is set, the loaded config is cached on disk and
+/// reused while none of the files it was built from change.
 pub fn load_config(path: Option<&str>) -> Result<Config> {
-    let config_path = path.unwrap_or(DEFAULT_CONFIG_PATH);
-    let path = Path::new(config_path);
+    let project_dir = match path {
+        Some(_) => PathBuf::new(),
+        None => find_project_dir(),
+    };
+    let use_cache = std::env::var_os(NO_CACHE_ENV).is_none_or(|v| v.is_empty());
+    let watched = watched_paths(path, &project_dir);
+    let cached = use_cache
+        .then(|| cache::read(path, &project_dir, &watched))
+        .flatten();
+    let mut config = match cached {
+        Some(config) => config,
+        None => {
+            let config = load_config_uncached(path, &project_dir)?;
+            if use_cache {
+                cache::write(path, &project_dir, &watched, &config);
+            }
+            config
+        }
+    };
+    if project_dir.is_absolute() {
+        config.project_dir = Some(project_dir);
+    }
+    Ok(config)
+}
 
-    if !path.exists() {
-        return Err(CchookedError::ConfigNotFound(config_path.to_string()));
+/// Finds the project whose `.claude` directory holds the config by walking
+/// up from the current directory.
+///
+/// Returns an empty (current directory relative) path if the config is in
+/// the current directory or was not found, and the absolute path of the
+/// parent directory otherwise. The search stops at a directory containing
+/// `.git` and never treats the home directory (which holds the global
+/// config) as a project.
+pub fn find_project_dir() -> PathBuf {
+    let has_config = |dir: &Path| {
+        dir.join(DEFAULT_CONFIG_PATH).is_file() || dir.join(DEFAULT_DROPIN_DIR).is_dir()
+    };
+    let Ok(cwd) = std::env::current_dir() else {
+        return PathBuf::new();
+    };
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    for (depth, dir) in cwd.ancestors().enumerate() {
+        if depth > 0 && home.as_deref() == Some(dir) {
+            break;
+        }
+        if has_config(dir) {
+            return match depth {
+                0 => PathBuf::new(),
+                _ => dir.to_path_buf(),
+            };
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
     }
+    PathBuf::new()
+}
 
-    let content = fs::read_to_string(path)?;
+/// Paths whose changes can affect the loaded config (besides the files
+/// actually read): the default config locations, which may appear later.
+fn watched_paths(path: Option<&str>, project_dir: &Path) -> Vec<PathBuf> {
+    match path {
+        Some(config_path) => vec![PathBuf::from(config_path)],
+        None => {
+            let mut paths = vec![
+                project_dir.join(DEFAULT_CONFIG_PATH),
+                project_dir.join(DEFAULT_DROPIN_DIR),
+            ];
+            paths.extend(global_config_candidates());
+            paths
+        }
+    }
+}
 
-    let config: Config = toml::from_str(&content).map_err(|e| CchookedError::ConfigParseError {
-        path: config_path.to_string(),
-        detail: e.to_string(),
+fn load_config_uncached(path: Option<&str>, project_dir: &Path) -> Result<Config> {
+    let mut config = match path {
+        Some(config_path) => {
+            if !Path::new(config_path).exists() {
+                return Err(CchookedError::ConfigNotFound(config_path.to_string()));
+            }
+            load_config_file(Path::new(config_path), &mut Vec::new())?
+        }
+        None => load_default_config(project_dir)?,
+    };
+    config.vars = resolve_vars(&config.vars).map_err(|detail| CchookedError::ConfigParseError {
+        path: match path {
+            Some(config_path) => config_path.to_string(),
+            None => project_dir.join(DEFAULT_CONFIG_PATH).display().to_string(),
+        },
+        detail,
     })?;
+    Ok(config)
+}
+
+/// Substitutes references between `[vars]` entries so that each value only
+/// refers to builtin variables.
+///
+/// Returns an error describing the cycle if a variable refers to itself,
+/// directly or through other variables.
+fn resolve_vars(
+    vars: &HashMap<String, String>,
+) -> std::result::Result<HashMap<String, String>, String> {
+    fn resolve(
+        name: &str,
+        vars: &HashMap<String, String>,
+        resolved: &mut HashMap<String, String>,
+        chain: &mut Vec<String>,
+    ) -> std::result::Result<String, String> {
+        if let Some(value) = resolved.get(name) {
+            return Ok(value.clone());
+        }
+        if chain.iter().any(|visited| visited == name) {
+            return Err(format!(
+                "variable '{name}' is defined recursively: {} -> {name}",
+                chain.join(" -> ")
+            ));
+        }
+        let Some(mut value) = vars.get(name).cloned() else {
+            return Ok(String::new());
+        };
+
+        chain.push(name.to_string());
+        let mut names: Vec<&String> = vars.keys().collect();
+        names.sort();
+        for other in names {
+            let placeholder = format!("${{{other}}}");
+            if value.contains(&placeholder) {
+                let other_value = resolve(other, vars, resolved, chain)?;
+                value = value.replace(&placeholder, &other_value);
+            }
+        }
+        chain.pop();
+
+        resolved.insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+
+    let mut resolved = HashMap::new();
+    let mut names: Vec<&String> = vars.keys().collect();
+    names.sort();
+    for name in names {
+        resolve(name, vars, &mut resolved, &mut Vec::new())?;
+    }
+    Ok(resolved)
+}
 
+fn load_default_config(project_dir: &Path) -> Result<Config> {
+    let main_path = &project_dir.join(DEFAULT_CONFIG_PATH);
+    let dropins = dropin_files(&project_dir.join(DEFAULT_DROPIN_DIR))?;
+
+    let project = if main_path.exists() || !dropins.is_empty() {
+        let mut config = if main_path.exists() {
+            load_config_file(main_path, &mut Vec::new())?
+        } else {
+            Config::default()
+        };
+        for dropin in dropins {
+            config.merge_unique(load_config_file(&dropin, &mut Vec::new())?)?;
+        }
+        Some(config)
+    } else {
+        None
+    };
+
+    let global_path = match &project {
+        Some(config) if config.ignore_global => None,
+        _ => global_config_path(),
+    };
+    let global = match global_path {
+        Some(path) => Some(load_config_file(&path, &mut Vec::new())?),
+        None => None,
+    };
+
+    match (global, project) {
+        (Some(global), Some(mut project)) => {
+            // プロジェクト側の同名ルールと settings がグローバル側より優先される（警告なし）
+            project.rule_order.extend(
+                global
+                    .rule_order
+                    .into_iter()
+                    .filter(|name| !project.rules.contains_key(name)),
+            );
+            for (name, rule) in global.rules {
+                if !project.rules.contains_key(&name) {
+                    if let Some(source) = global.rule_sources.get(&name) {
+                        project.rule_sources.insert(name.clone(), source.clone());
+                    }
+                    project.rules.insert(name, rule);
+                }
+            }
+            project.source_files.extend(global.source_files);
+            project.warnings.extend(global.warnings);
+            project.merge_vars(global.vars);
+            for (profile_name, profile) in global.profiles {
+                let merged = project.profiles.entry(profile_name).or_default();
+                for (name, rule) in profile.rules {
+                    merged.rules.entry(name).or_insert(rule);
+                }
+            }
+            Ok(project)
+        }
+        (Some(config), None) | (None, Some(config)) => Ok(config),
+        (None, None) => {
+            let mut tried = vec![main_path.display().to_string()];
+            tried.extend(
+                global_config_candidates()
+                    .iter()
+                    .map(|p| p.display().to_string()),
+            );
+            Err(CchookedError::ConfigNotFound(tried.join(", ")))
+        }
+    }
+}
+
+/// Returns the path of the user-global config file if it exists.
+fn global_config_path() -> Option<PathBuf> {
+    global_config_candidates()
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+/// Locations of the user-global config file, in order of preference.
+///
+/// On Linux the XDG location `$XDG_CONFIG_HOME/cchooked/hooks-rules.toml`
+/// (default `~/.config/cchooked/hooks-rules.toml`) is preferred over
+/// `~/.claude/hooks-rules.toml`. Elsewhere `~/.claude/hooks-rules.toml` comes
+/// first and the XDG location is only tried if `XDG_CONFIG_HOME` is set.
+/// Missing `HOME` or `XDG_CONFIG_HOME` simply leave out their candidates.
+fn global_config_candidates() -> Vec<PathBuf> {
+    let non_empty = |name: &str| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let home = non_empty("HOME");
+    let claude = home
+        .as_ref()
+        .map(|home| home.join(".claude").join("hooks-rules.toml"));
+    let xdg_config_home = non_empty("XDG_CONFIG_HOME");
+
+    let candidates = if cfg!(target_os = "linux") {
+        let xdg = xdg_config_home.or_else(|| home.map(|home| home.join(".config")));
+        [
+            xdg.map(|dir| dir.join("cchooked").join("hooks-rules.toml")),
+            claude,
+        ]
+    } else {
+        [
+            claude,
+            xdg_config_home.map(|dir| dir.join("cchooked").join("hooks-rules.toml")),
+        ]
+    };
+    candidates.into_iter().flatten().collect()
+}
+
+/// Lists the `*.toml` files in a drop-in directory, sorted by filename.
+/// A missing directory has no drop-ins.
+fn dropin_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "toml") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Loads a config file and its includes. `stack` holds the files currently
+/// being loaded and is used to detect include cycles.
+fn load_config_file(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Config> {
+    let display_path = path.display().to_string();
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        let chain: Vec<String> = stack
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(CchookedError::ConfigParseError {
+            path: display_path,
+            detail: format!("include cycle detected: {}", chain.join(" -> ")),
+        });
+    }
+
+    let content = fs::read_to_string(path)?;
+    let parse_error = |e: toml::de::Error| CchookedError::ConfigParseError {
+        path: display_path.clone(),
+        detail: e.to_string(),
+    };
+    let table: toml::Table = toml::from_str(&content).map_err(parse_error)?;
+    // 新しい形式の設定は他のエラーより先に「cchooked が古い」と伝える
+    let schema_warning = match table.get("schema").and_then(toml::Value::as_integer) {
+        Some(schema) => check_schema(schema, &display_path)?,
+        None => None,
+    };
+    if let Some(required) = table.get("min_version").and_then(toml::Value::as_str) {
+        check_min_version(required).map_err(|detail| CchookedError::ConfigParseError {
+            path: display_path.clone(),
+            detail,
+        })?;
+    }
+    let parsed: std::result::Result<Config, _> = toml::from_str(&content);
+    // 型エラーより未知のフィールドの方が原因として分かりやすいため先に報告する
+    let allow_unknown_fields = match &parsed {
+        Ok(config) => config.settings.allow_unknown_fields,
+        Err(_) => table
+            .get("settings")
+            .and_then(|settings| settings.get("allow_unknown_fields"))
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(false),
+    };
+    if !allow_unknown_fields && let Some(unknown) = find_unknown_field(&table) {
+        return Err(CchookedError::ConfigParseError {
+            path: display_path,
+            detail: unknown,
+        });
+    }
+    let mut config = parsed.map_err(parse_error)?;
+    config
+        .settings
+        .validate()
+        .map_err(|detail| CchookedError::ConfigParseError {
+            path: display_path.clone(),
+            detail,
+        })?;
+    let mut var_names: Vec<&String> = config.vars.keys().collect();
+    var_names.sort();
+    if let Some(name) = var_names
+        .into_iter()
+        .find(|name| BUILTIN_VARS.contains(&name.as_str()))
+    {
+        return Err(CchookedError::ConfigParseError {
+            path: display_path,
+            detail: format!("[vars] '{name}' conflicts with the builtin variable of the same name"),
+        });
+    }
+    for ordered in std::mem::take(&mut config.rule_list) {
+        if config.rules.contains_key(&ordered.name) {
+            return Err(CchookedError::ConfigParseError {
+                path: display_path,
+                detail: format!("rule '{}' is defined more than once", ordered.name),
+            });
+        }
+        config.rule_order.push(ordered.name.clone());
+        config.rules.insert(ordered.name, ordered.config);
+    }
+    config.source_files.push(path.to_path_buf());
+    config.warnings.extend(schema_warning);
+    config.rule_sources = config
+        .rules
+        .keys()
+        .map(|name| (name.clone(), display_path.clone()))
+        .collect();
+
+    if config.include.is_empty() {
+        return Ok(config);
+    }
+
+    stack.push(canonical);
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let own_rules = Config {
+        rules: std::mem::take(&mut config.rules),
+        rule_sources: std::mem::take(&mut config.rule_sources),
+        rule_order: std::mem::take(&mut config.rule_order),
+        ..Config::default()
+    };
+    for include in std::mem::take(&mut config.include) {
+        let include_path = base_dir.join(&include);
+        if !include_path.exists() {
+            return Err(CchookedError::ConfigParseError {
+                path: display_path,
+                detail: format!(
+                    "included file '{include}' not found: {}",
+                    include_path.display()
+                ),
+            });
+        }
+        config.merge(load_config_file(&include_path, stack)?);
+    }
+    stack.pop();
+
+    config.merge(own_rules);
     Ok(config)
 }
+
+/// Checks a config file's `schema` version against the supported one.
+///
+/// Newer schemas are rejected; older ones are read as the current schema,
+/// and the warning for them is returned.
+fn check_schema(schema: i64, path: &str) -> Result<Option<String>> {
+    if schema > SCHEMA_VERSION {
+        return Err(CchookedError::ConfigParseError {
+            path: path.to_string(),
+            detail: format!(
+                "config schema {schema} is newer than the supported schema {SCHEMA_VERSION}; upgrade cchooked to use this config"
+            ),
+        });
+    }
+    Ok((schema < SCHEMA_VERSION).then(|| {
+        format!("{path} uses config schema {schema}; it is read as schema {SCHEMA_VERSION}")
+    }))
+}
+
+/// Checks that this cchooked is at least the config's `min_version`.
+fn check_min_version(required: &str) -> std::result::Result<(), String> {
+    let required_version =
+        Version::parse(required).map_err(|detail| format!("min_version: {detail}"))?;
+    let current = env!("CARGO_PKG_VERSION");
+    match Version::parse(current) {
+        Ok(current_version) if current_version < required_version => Err(format!(
+            "this config requires cchooked >= {required}, you have {current}"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the field names a config struct accepts.
+///
+/// Derived `Deserialize` impls pass their field list to
+/// `deserialize_struct`; this deserializer records it and stops.
+fn known_fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    struct FieldCollector<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> serde::Deserializer<'de> for FieldCollector<'_> {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(
+            self,
+            _visitor: V,
+        ) -> std::result::Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> std::result::Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(serde::de::Error::custom("fields collected"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldCollector(&mut fields));
+    fields
+}
+
+/// Finds the first key in a parsed config file that no config struct
+/// accepts, and describes it (with a suggestion for likely typos).
+fn find_unknown_field(table: &toml::Table) -> Option<String> {
+    if let Some(message) = check_fields::<Config>(table, "", "the top level") {
+        return Some(message);
+    }
+    let ordered_rules = table
+        .get("rule")
+        .and_then(toml::Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let unknown = ordered_rules.iter().enumerate().find_map(|(i, rule)| {
+        let mut rule = rule.as_table()?.clone();
+        let location = match rule.remove("name") {
+            Some(toml::Value::String(name)) => format!("rule '{name}'"),
+            _ => format!("[[rule]] #{}", i + 1),
+        };
+        check_rule_fields(&rule, &location)
+    });
+    if unknown.is_some() {
+        return unknown;
+    }
+
+    let tests = table
+        .get("tests")
+        .and_then(toml::Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let unknown = tests.iter().enumerate().find_map(|(i, test)| {
+        let test = test.as_table()?;
+        let location = match test.get("name").and_then(toml::Value::as_str) {
+            Some(name) => format!("test '{name}'"),
+            None => format!("[[tests]] #{}", i + 1),
+        };
+        check_fields::<ConfigTest>(test, "", &location)
+    });
+    if unknown.is_some() {
+        return unknown;
+    }
+
+    if let Some(message) = nested_table(table, "rules").and_then(|rules| check_rules(rules, "")) {
+        return Some(message);
+    }
+
+    let profiles = nested_table(table, "profiles")?;
+    let mut profile_names: Vec<&String> = profiles.keys().collect();
+    profile_names.sort();
+    profile_names.into_iter().find_map(|profile_name| {
+        let profile = profiles.get(profile_name).and_then(toml::Value::as_table)?;
+        let location = format!("profile '{profile_name}'");
+        check_fields::<ProfileConfig>(profile, "", &location).or_else(|| {
+            nested_table(profile, "rules")
+                .and_then(|rules| check_rules(rules, &format!(" in {location}")))
+        })
+    })
+}
+
+/// Checks each rule of a `[rules]` table; `suffix` is appended to the
+/// location in messages.
+fn check_rules(rules: &toml::Table, suffix: &str) -> Option<String> {
+    let mut names: Vec<&String> = rules.keys().collect();
+    names.sort();
+    names.into_iter().find_map(|name| {
+        let rule = rules.get(name).and_then(toml::Value::as_table)?;
+        check_rule_fields(rule, &format!("rule '{name}'{suffix}"))
+    })
+}
+
+fn check_rule_fields(rule: &toml::Table, location: &str) -> Option<String> {
+    check_fields::<RuleConfig>(rule, "", location)
+        .or_else(|| {
+            nested_table(rule, "matcher_mcp")
+                .and_then(|mcp| check_fields::<McpMatcherConfig>(mcp, "matcher_mcp.", location))
+        })
+        .or_else(|| {
+            nested_table(rule, "examples").and_then(|examples| {
+                check_fields::<ExamplesConfig>(examples, "examples.", location)
+            })
+        })
+        .or_else(|| nested_table(rule, "when").and_then(|when| check_when(when, "when", location)))
+}
+
+fn check_when(when: &toml::Table, prefix: &str, location: &str) -> Option<String> {
+    if let Some(message) = check_fields::<WhenConfig>(when, &format!("{prefix}."), location) {
+        return Some(message);
+    }
+    let nested_fields = check_nested::<ParsedConfig>(when, "parsed", prefix, location)
+        .or_else(|| check_nested::<TimeConfig>(when, "time", prefix, location))
+        .or_else(|| check_nested::<WhenNotConfig>(when, "not", prefix, location));
+    if nested_fields.is_some() {
+        return nested_fields;
+    }
+    ["any", "all"].into_iter().find_map(|key| {
+        let groups = when.get(key).and_then(toml::Value::as_array)?;
+        groups.iter().enumerate().find_map(|(i, group)| {
+            let group = group.as_table()?;
+            check_when(group, &format!("{prefix}.{key}[{i}]"), location)
+        })
+    })
+}
+
+fn check_nested<'de, T: Deserialize<'de>>(
+    when: &toml::Table,
+    key: &str,
+    prefix: &str,
+    location: &str,
+) -> Option<String> {
+    let table = nested_table(when, key)?;
+    check_fields::<T>(table, &format!("{prefix}.{key}."), location)
+}
+
+fn nested_table<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Table> {
+    table.get(key).and_then(toml::Value::as_table)
+}
+
+fn check_fields<'de, T: Deserialize<'de>>(
+    table: &toml::Table,
+    prefix: &str,
+    location: &str,
+) -> Option<String> {
+    let fields = known_fields::<T>();
+    let mut keys: Vec<&String> = table.keys().collect();
+    keys.sort();
+    let unknown = keys
+        .into_iter()
+        .find(|key| !fields.contains(&key.as_str()))?;
+    let mut message = format!("unknown field '{prefix}{unknown}' in {location}");
+    if let Some(suggestion) = suggest_field(unknown, fields) {
+        message.push_str(&format!(" (did you mean '{prefix}{suggestion}'?)"));
+    }
+    Some(message)
+}
+
+/// Suggests the known field closest to `unknown` by edit distance, if it is
+/// close enough to be a plausible typo.
+fn suggest_field(unknown: &str, fields: &[&'static str]) -> Option<&'static str> {
+    let max_distance = (unknown.chars().count() / 3).max(1);
+    fields
+        .iter()
+        .map(|field| (edit_distance(unknown, field), *field))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, field)| field)
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous.get(j).copied().unwrap_or(0) + usize::from(ca != *cb);
+            let insertion = current.get(j).copied().unwrap_or(0) + 1;
+            let deletion = previous.get(j + 1).copied().unwrap_or(0) + 1;
+            current.push(substitution.min(insertion).min(deletion));
+        }
+        previous = current;
+    }
+    previous.last().copied().unwrap_or(0)
+}
@@ -1,8 +1,14 @@
 use crate::rule::HookInput;
+use crate::style;
+use regex_lite::Regex;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 
 /// Execution context containing extracted input values and environment information.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Context {
     /// Command string from tool input.
     pub command: String,
@@ -12,16 +18,120 @@ pub struct Context {
     pub file_dir: String,
     /// Name of the tool being invoked.
     pub tool_name: String,
-    /// Current git branch name.
-    pub branch: String,
-    /// Current working directory of cchooked.
+    /// MCP server name parsed from `mcp__<server>__<tool>` (empty otherwise).
+    pub mcp_server: String,
+    /// MCP tool name parsed from `mcp__<server>__<tool>` (empty otherwise).
+    pub mcp_tool: String,
+    /// Subagent type for the Task tool (empty when absent).
+    pub subagent_type: String,
+    /// Short task description for the Task tool (empty when absent).
+    pub task_description: String,
+    /// Exit code from the tool response (empty when absent).
+    pub tool_exit_code: String,
+    /// Permission mode reported by Claude Code (empty when absent).
+    pub permission_mode: String,
+    /// Model or agent identity (empty when absent).
+    pub model: String,
+    /// Path to the session transcript.
+    pub transcript_path: Option<String>,
+    /// Cached transcript tails, keyed by the number of bytes read.
+    pub transcript_cache: RefCell<HashMap<u64, Option<String>>>,
+    /// Whether a transcript read failure has already been reported.
+    pub transcript_warned: Cell<bool>,
+    /// File content from the Write tool input.
+    pub content: Option<String>,
+    /// Whether the content or target file looks binary (detected lazily).
+    pub is_binary: OnceCell<bool>,
+    /// Whether file_path lies outside workspace_root (None without a file path).
+    pub outside_workspace: OnceCell<Option<bool>>,
+    /// Current git branch name (detected lazily on first access).
+    pub branch: OnceCell<String>,
+    /// Whether the working tree has uncommitted changes (detected lazily).
+    pub git_dirty: OnceCell<bool>,
+    /// Whether file_path is ignored by git (detected lazily).
+    pub file_in_gitignore: OnceCell<bool>,
+    /// Whether file_path is tracked by git (detected lazily).
+    pub file_tracked: OnceCell<bool>,
+    /// URL of the origin remote (detected lazily, empty if none).
+    pub remote: OnceCell<String>,
+    /// Current login user name (detected lazily, empty if unknown).
+    pub user: OnceCell<String>,
+    /// Machine hostname (detected lazily, empty if unknown).
+    pub hostname: OnceCell<String>,
+    /// Workspace root (CLAUDE_PROJECT_DIR, then the input cwd, then the current directory).
     pub workspace_root: String,
+    /// Canonicalized workspace_root (resolved lazily on first access).
+    pub canonical_workspace_root: OnceCell<String>,
+    /// Cached results of path existence checks, keyed by resolved path.
+    pub path_exists_cache: RefCell<HashMap<String, bool>>,
+    /// Cached workspace listings (relative paths), keyed by search depth.
+    pub workspace_entries_cache: RefCell<HashMap<usize, Vec<String>>>,
+    /// Snapshot of the process environment (read lazily on first access).
+    pub env: OnceCell<HashMap<String, String>>,
+    /// Values captured by the matched rule.
+    pub values: MatchValues,
+    /// Block rules bypassed via `allow_if_env`, with the variable that
+    /// allowed each (filled in during evaluation).
+    pub bypassed: Vec<(String, String)>,
+    /// User-defined template variables from `[vars]` (resolved against each other).
+    pub vars: HashMap<String, String>,
+    /// Project directory found above the current directory (relative log paths resolve against it).
+    pub project_dir: Option<PathBuf>,
+}
+
+/// Names of the builtin template variables expanded by [`Context::expand`].
+pub const BUILTIN_VARS: &[&str] = &[
+    "command",
+    "file_path",
+    "file_dir",
+    "file_path_rel",
+    "tool_name",
+    "mcp_server",
+    "mcp_tool",
+    "subagent_type",
+    "task_description",
+    "tool_exit_code",
+    "permission_mode",
+    "model",
+    "os",
+    "branch",
+    "remote",
+    "user",
+    "hostname",
+    "is_binary",
+    "ci",
+    "workspace_root",
+    "rule_name",
+    "matched_segment",
+    "content_match",
+    "added_text",
+    "removed_text",
+];
+
+/// Values captured while matching a rule, exposed as template variables.
+#[derive(Debug, Clone, Default)]
+pub struct MatchValues {
+    /// Name of the rule being matched.
+    pub rule_name: Option<String>,
+    /// Command segment matched by `when.command` (truncated).
+    pub matched_segment: Option<String>,
+    /// Substring of the Write content matched by `when.content` (truncated).
+    pub content_match: Option<String>,
+    /// Lines added by the edit matched by `when.added_text` (truncated).
+    pub added_text: Option<String>,
+    /// Lines removed by the edit matched by `when.removed_text` (truncated).
+    pub removed_text: Option<String>,
+    /// Groups captured by `when.command` (or else `when.file_path`), the
+    /// whole match first, exposed as `${0}`, `${1}`, ... (truncated). `None`
+    /// unless the rule refers to a capture group.
+    pub groups: Option<Vec<String>>,
 }
 
 impl Context {
     /// Creates a new context from hook input.
     ///
-    /// Extracts command, file path, tool name, and detects the current git branch.
+    /// Extracts command, file path, and tool name. The current git branch is
+    /// detected lazily the first time it is needed.
     pub fn from_input(input: &HookInput) -> Self {
         let file_path = input.tool_input.file_path.clone().unwrap_or_default();
         let file_dir = if file_path.is_empty() {
@@ -35,33 +145,592 @@ impl Context {
         let workspace_root = std::env::var("CLAUDE_PROJECT_DIR")
             .ok()
             .filter(|s| !s.is_empty())
+            .or_else(|| input.cwd.clone().filter(|s| !s.is_empty()))
             .unwrap_or_else(|| {
                 std::env::current_dir()
                     .map(|p| p.to_string_lossy().to_string())
                     .unwrap_or_default()
             });
 
+        let (mcp_server, mcp_tool) = parse_mcp_tool_name(&input.tool_name).unwrap_or_default();
+
         Self {
             command: input.tool_input.command.clone().unwrap_or_default(),
             file_path,
             file_dir,
             tool_name: input.tool_name.clone(),
-            branch: get_current_branch().unwrap_or_default(),
+            mcp_server: mcp_server.to_string(),
+            mcp_tool: mcp_tool.to_string(),
+            subagent_type: input.tool_input.subagent_type.clone().unwrap_or_default(),
+            task_description: input.tool_input.description.clone().unwrap_or_default(),
+            tool_exit_code: input
+                .tool_exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_default(),
+            permission_mode: input.permission_mode.clone().unwrap_or_default(),
+            model: input.model.clone().unwrap_or_default(),
+            transcript_path: input.transcript_path.clone(),
+            transcript_cache: RefCell::new(HashMap::new()),
+            transcript_warned: Cell::new(false),
+            content: input.tool_input.content.clone(),
+            is_binary: OnceCell::new(),
+            outside_workspace: OnceCell::new(),
+            branch: OnceCell::new(),
+            git_dirty: OnceCell::new(),
+            file_in_gitignore: OnceCell::new(),
+            file_tracked: OnceCell::new(),
+            remote: OnceCell::new(),
+            user: OnceCell::new(),
+            hostname: OnceCell::new(),
             workspace_root,
+            canonical_workspace_root: OnceCell::new(),
+            path_exists_cache: RefCell::new(HashMap::new()),
+            workspace_entries_cache: RefCell::new(HashMap::new()),
+            env: OnceCell::new(),
+            values: MatchValues::default(),
+            bypassed: Vec::new(),
+            vars: HashMap::new(),
+            project_dir: None,
+        }
+    }
+
+    /// Records the project directory found above the current directory and
+    /// uses it as workspace_root unless CLAUDE_PROJECT_DIR is set.
+    pub fn set_project_dir(&mut self, project_dir: &Path) {
+        let project_dir_env = std::env::var("CLAUDE_PROJECT_DIR").unwrap_or_default();
+        if project_dir_env.is_empty() {
+            self.workspace_root = project_dir.to_string_lossy().to_string();
+        }
+        self.project_dir = Some(project_dir.to_path_buf());
+    }
+
+    /// Returns a copy of this context carrying the values captured by a matched rule.
+    pub fn with_values(&self, values: &MatchValues) -> Self {
+        Self {
+            values: values.clone(),
+            ..self.clone()
         }
     }
 
+    /// Returns the current git branch, running git on first access.
+    pub fn branch(&self) -> &str {
+        self.branch
+            .get_or_init(|| get_current_branch().unwrap_or_default())
+    }
+
+    /// Returns true if the working tree in workspace_root has uncommitted changes.
+    ///
+    /// Runs `git status --porcelain` on first access; directories outside a git
+    /// repository are treated as clean.
+    pub fn git_dirty(&self) -> bool {
+        *self.git_dirty.get_or_init(|| {
+            git_output(&self.workspace_root, &["status", "--porcelain"])
+                .is_some_and(|output| !output.is_empty())
+        })
+    }
+
+    /// Returns true if file_path is ignored by git, running git on first access.
+    ///
+    /// Returns false for an empty file_path, outside a git repository, or when
+    /// git is unavailable.
+    pub fn file_in_gitignore(&self) -> bool {
+        *self.file_in_gitignore.get_or_init(|| {
+            !self.file_path.is_empty()
+                && git_output(
+                    &self.workspace_root,
+                    &["check-ignore", "-q", "--", &self.file_path],
+                )
+                .is_some()
+        })
+    }
+
+    /// Returns true if file_path is tracked by git, running git on first access.
+    ///
+    /// Returns false for an empty file_path, outside a git repository, or when
+    /// git is unavailable.
+    pub fn file_tracked(&self) -> bool {
+        *self.file_tracked.get_or_init(|| {
+            !self.file_path.is_empty()
+                && git_output(
+                    &self.workspace_root,
+                    &["ls-files", "--error-unmatch", "--", &self.file_path],
+                )
+                .is_some()
+        })
+    }
+
+    /// Returns the URL of the origin remote, running git on first access.
+    ///
+    /// Returns an empty string outside a git repository or without an origin.
+    pub fn remote(&self) -> &str {
+        self.remote.get_or_init(|| {
+            git_output(&self.workspace_root, &["remote", "get-url", "origin"]).unwrap_or_default()
+        })
+    }
+
+    /// Returns the current login user name, looking it up on first access.
+    pub fn user(&self) -> &str {
+        self.user
+            .get_or_init(|| resolve_user(self.env_vars(), whoami))
+    }
+
+    /// Returns the machine hostname, looking it up on first access.
+    pub fn hostname(&self) -> &str {
+        self.hostname
+            .get_or_init(|| resolve_hostname(self.env_vars(), system_hostname))
+    }
+
+    /// Returns workspace_root with symlinks resolved.
+    ///
+    /// Falls back to workspace_root as-is when it cannot be canonicalized.
+    pub fn canonical_workspace_root(&self) -> &str {
+        self.canonical_workspace_root.get_or_init(|| {
+            std::fs::canonicalize(&self.workspace_root)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| self.workspace_root.clone())
+        })
+    }
+
+    /// Returns whether file_path lies outside workspace_root.
+    ///
+    /// Relative paths are resolved against workspace_root, `..` components are
+    /// removed lexically, and symlinks are resolved for the longest existing
+    /// prefix of both paths. Returns None when there is no file path or
+    /// workspace root.
+    pub fn outside_workspace(&self) -> Option<bool> {
+        *self.outside_workspace.get_or_init(|| {
+            if self.file_path.is_empty() || self.workspace_root.is_empty() {
+                return None;
+            }
+            let root =
+                canonicalize_existing_prefix(&normalize_lexically(Path::new(&self.workspace_root)));
+            let path = canonicalize_existing_prefix(&normalize_lexically(
+                &self.resolve_path(&self.file_path),
+            ));
+            Some(!path.starts_with(root))
+        })
+    }
+
+    /// Returns the last `max_bytes` of the session transcript.
+    ///
+    /// Returns None (warning once per invocation) if the transcript is missing
+    /// or unreadable. Results are cached per byte limit.
+    pub fn transcript_tail(&self, max_bytes: u64) -> Option<String> {
+        if let Some(cached) = self.transcript_cache.borrow().get(&max_bytes) {
+            return cached.clone();
+        }
+        let tail = match self.transcript_path.as_deref() {
+            Some(path) => read_tail(Path::new(path), max_bytes).map_err(|e| e.to_string()),
+            None => Err("hook input has no transcript_path".to_string()),
+        };
+        let tail = match tail {
+            Ok(tail) => Some(tail),
+            Err(detail) => {
+                if !self.transcript_warned.replace(true) {
+                    eprintln!(
+                        "{}",
+                        style::warning(&format!(
+                            "Warning: could not read transcript for when.transcript: {detail}"
+                        ))
+                    );
+                }
+                None
+            }
+        };
+        self.transcript_cache
+            .borrow_mut()
+            .insert(max_bytes, tail.clone());
+        tail
+    }
+
+    /// Returns true if any entry under workspace_root matches one of the globs.
+    ///
+    /// Entries are listed down to `depth` nested directories (0 means the
+    /// workspace root only) and matched by their `/`-separated relative path.
+    /// Listings are cached per depth for the lifetime of the context.
+    pub fn workspace_has(&self, globs: &[Regex], depth: usize) -> bool {
+        let mut cache = self.workspace_entries_cache.borrow_mut();
+        let entries = cache.entry(depth).or_insert_with(|| {
+            let mut entries = Vec::new();
+            if !self.workspace_root.is_empty() {
+                list_entries(Path::new(&self.workspace_root), "", depth, &mut entries);
+            }
+            entries
+        });
+        entries
+            .iter()
+            .any(|entry| globs.iter().any(|g| g.is_match(entry)))
+    }
+
+    /// Returns true if the Write content, or otherwise the existing file at
+    /// file_path, looks binary.
+    ///
+    /// Inputs without content whose file does not exist are not binary.
+    pub fn is_binary(&self) -> bool {
+        *self.is_binary.get_or_init(|| match &self.content {
+            Some(content) => crate::binary::is_binary_text(content),
+            None if !self.file_path.is_empty() => {
+                crate::binary::is_binary_file(&self.resolve_path(&self.file_path))
+            }
+            None => false,
+        })
+    }
+
+    /// Resolves a relative path against workspace_root.
+    fn resolve_path(&self, path: &str) -> PathBuf {
+        let path = Path::new(path);
+        if path.is_absolute() || self.workspace_root.is_empty() {
+            path.to_path_buf()
+        } else {
+            Path::new(&self.workspace_root).join(path)
+        }
+    }
+
+    /// Returns the value of an environment variable, or None if it is unset.
+    ///
+    /// The environment is read once per context; variables whose names or
+    /// values are not valid UTF-8 are treated as unset.
+    pub fn env_var(&self, name: &str) -> Option<&str> {
+        self.env_vars().get(name).map(String::as_str)
+    }
+
+    fn env_vars(&self) -> &HashMap<String, String> {
+        self.env.get_or_init(|| {
+            std::env::vars_os()
+                .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+                .collect()
+        })
+    }
+
+    /// Replaces each `${env:NAME}` with the value of the environment
+    /// variable NAME, or an empty string if it is unset. References whose
+    /// name is not a valid variable name are left as they are.
+    ///
+    /// This runs before the values taken from the hook input are inserted,
+    /// so a command or file path containing `${env:...}` cannot read the
+    /// environment.
+    fn expand_env(&self, template: &str) -> String {
+        const PREFIX: &str = "${env:";
+        let mut expanded = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find(PREFIX) {
+            let (before, reference) = rest.split_at(start);
+            expanded.push_str(before);
+            let name = reference
+                .get(PREFIX.len()..)
+                .and_then(|after| after.split_once('}'))
+                .map(|(name, _)| name)
+                .filter(|name| is_env_name(name));
+            match name {
+                Some(name) => {
+                    expanded.push_str(self.env_var(name).unwrap_or_default());
+                    rest = reference
+                        .get(PREFIX.len() + name.len() + 1..)
+                        .unwrap_or_default();
+                }
+                None => {
+                    expanded.push_str(PREFIX);
+                    rest = reference.get(PREFIX.len()..).unwrap_or_default();
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Returns true if cchooked is running under CI.
+    pub fn ci(&self) -> bool {
+        is_ci(self.env_vars())
+    }
+
+    /// Returns true if the given path template exists on disk.
+    ///
+    /// The template is expanded and relative paths are resolved against
+    /// workspace_root. Results are cached for the lifetime of the context.
+    pub fn path_exists(&self, template: &str) -> bool {
+        let resolved = self.resolve_path(&self.expand(template));
+        let key = resolved.to_string_lossy().to_string();
+        if let Some(&exists) = self.path_exists_cache.borrow().get(&key) {
+            return exists;
+        }
+        let exists = resolved.exists();
+        self.path_exists_cache.borrow_mut().insert(key, exists);
+        exists
+    }
+
     /// Expands template variables in a string.
     ///
-    /// Replaces `${command}`, `${file_path}`, `${file_dir}`, `${tool_name}`, `${branch}`, and `${workspace_root}` with their values.
+    /// Replaces `${command}`, `${file_path}`, `${file_dir}`, `${file_path_rel}`,
+    /// `${tool_name}`, `${mcp_server}`,
+    /// `${mcp_tool}`, `${subagent_type}`, `${task_description}`, `${tool_exit_code}`,
+    /// `${permission_mode}`, `${model}`, `${os}`,
+    /// `${branch}`, `${remote}`, `${user}`, `${hostname}`, `${ci}`, `${is_binary}`,
+    /// `${workspace_root}`, `${rule_name}`, `${matched_segment}`, `${content_match}`,
+    /// `${added_text}`, and `${removed_text}` with their values. User-defined
+    /// `[vars]` are expanded first, so their values may refer to the builtin
+    /// variables, and then `${env:NAME}` (see [`Context::expand_env`]) and the
+    /// capture groups `${0}`, `${1}`, ... (see [`Context::expand_groups`]).
     pub fn expand(&self, template: &str) -> String {
-        template
+        let mut expanded = template.to_string();
+        for (name, value) in &self.vars {
+            expanded = expanded.replace(&format!("${{{name}}}"), value);
+        }
+        let expanded = self
+            .expand_groups(&self.expand_env(&expanded))
             .replace("${command}", &self.command)
             .replace("${file_path}", &self.file_path)
             .replace("${file_dir}", &self.file_dir)
             .replace("${tool_name}", &self.tool_name)
-            .replace("${branch}", &self.branch)
+            .replace("${mcp_server}", &self.mcp_server)
+            .replace("${mcp_tool}", &self.mcp_tool)
+            .replace("${subagent_type}", &self.subagent_type)
+            .replace("${task_description}", &self.task_description)
+            .replace("${tool_exit_code}", &self.tool_exit_code)
+            .replace("${permission_mode}", &self.permission_mode)
+            .replace("${model}", &self.model)
+            .replace("${os}", std::env::consts::OS);
+        let expanded = if expanded.contains("${file_path_rel}") {
+            expanded.replace(
+                "${file_path_rel}",
+                &relative_to_workspace(&self.file_path, &self.workspace_root),
+            )
+        } else {
+            expanded
+        };
+        let expanded = if expanded.contains("${branch}") {
+            expanded.replace("${branch}", self.branch())
+        } else {
+            expanded
+        };
+        let expanded = if expanded.contains("${remote}") {
+            expanded.replace("${remote}", self.remote())
+        } else {
+            expanded
+        };
+        let expanded = if expanded.contains("${user}") {
+            expanded.replace("${user}", self.user())
+        } else {
+            expanded
+        };
+        let expanded = if expanded.contains("${hostname}") {
+            expanded.replace("${hostname}", self.hostname())
+        } else {
+            expanded
+        };
+        let expanded = if expanded.contains("${is_binary}") {
+            expanded.replace(
+                "${is_binary}",
+                if self.is_binary() { "true" } else { "false" },
+            )
+        } else {
+            expanded
+        };
+        let expanded = if expanded.contains("${ci}") {
+            expanded.replace("${ci}", if self.ci() { "true" } else { "false" })
+        } else {
+            expanded
+        };
+        expanded
             .replace("${workspace_root}", &self.workspace_root)
+            .replace(
+                "${rule_name}",
+                self.values.rule_name.as_deref().unwrap_or(""),
+            )
+            .replace(
+                "${matched_segment}",
+                self.values.matched_segment.as_deref().unwrap_or(""),
+            )
+            .replace(
+                "${content_match}",
+                self.values.content_match.as_deref().unwrap_or(""),
+            )
+            .replace(
+                "${added_text}",
+                self.values.added_text.as_deref().unwrap_or(""),
+            )
+            .replace(
+                "${removed_text}",
+                self.values.removed_text.as_deref().unwrap_or(""),
+            )
+    }
+
+    /// Replaces each `${N}` with capture group N of the matched condition,
+    /// or an empty string if there is no such group (including rules whose
+    /// conditions captured nothing). Does nothing unless the rule refers to
+    /// a capture group.
+    ///
+    /// Like [`Context::expand_env`], this runs before the values taken from
+    /// the hook input are inserted, so a `${N}` in a command is kept as is.
+    fn expand_groups(&self, template: &str) -> String {
+        let Some(groups) = &self.values.groups else {
+            return template.to_string();
+        };
+        let mut expanded = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("${") {
+            let (before, reference) = rest.split_at(start);
+            expanded.push_str(before);
+            let after = reference.get(2..).unwrap_or_default();
+            match group_reference(after) {
+                Some((index, len)) => {
+                    expanded.push_str(groups.get(index).map_or("", String::as_str));
+                    rest = after.get(len..).unwrap_or_default();
+                }
+                None => {
+                    expanded.push_str("${");
+                    rest = after;
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+/// Parses the group number of a `${N}` reference from the text after `${`,
+/// returning it with the length of `N}`.
+fn group_reference(after: &str) -> Option<(usize, usize)> {
+    let digits = after.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 || after.as_bytes().get(digits) != Some(&b'}') {
+        return None;
+    }
+    let index = after.get(..digits)?.parse().ok()?;
+    Some((index, digits + 1))
+}
+
+/// Returns true if `template` refers to a capture group as `${N}`.
+pub fn uses_capture_groups(template: &str) -> bool {
+    template
+        .match_indices("${")
+        .any(|(start, _)| group_reference(template.get(start + 2..).unwrap_or_default()).is_some())
+}
+
+/// Returns true if `name` can be an environment variable name: ASCII
+/// letters, digits and underscores, not starting with a digit.
+fn is_env_name(name: &str) -> bool {
+    name.bytes()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == b'_')
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Splits an MCP tool name of the form `mcp__<server>__<tool>`.
+///
+/// Returns None for names without that shape.
+pub fn parse_mcp_tool_name(tool_name: &str) -> Option<(&str, &str)> {
+    let (server, tool) = tool_name.strip_prefix("mcp__")?.split_once("__")?;
+    (!server.is_empty() && !tool.is_empty()).then_some((server, tool))
+}
+
+/// Removes `.` and `..` components without touching the file system.
+///
+/// `..` at the root is dropped, so the result never escapes the root.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() && !path.has_root() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Resolves symlinks in the longest existing prefix of `path`.
+///
+/// The remaining, non-existent components are appended unchanged.
+fn canonicalize_existing_prefix(path: &Path) -> PathBuf {
+    let mut prefix = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = std::fs::canonicalize(prefix) {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |acc, part| acc.join(part));
+        }
+        match (prefix.parent(), prefix.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                prefix = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// Reads at most the last `max_bytes` of a file without reading the rest.
+fn read_tail(path: &Path, max_bytes: u64) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(max_bytes)))?;
+    let mut buffer = Vec::new();
+    file.take(max_bytes).read_to_end(&mut buffer)?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+/// Directory names skipped when listing nested workspace entries.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// Appends the relative paths of entries under `dir` to `entries`,
+/// descending at most `depth` more levels. Hidden and dependency directories
+/// are not descended into.
+fn list_entries(dir: &Path, prefix: &str, depth: usize, entries: &mut Vec<String>) {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative = format!("{prefix}{name}");
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+        if depth > 0 && is_dir && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+            list_entries(&entry.path(), &format!("{relative}/"), depth - 1, entries);
+        }
+        entries.push(relative);
+    }
+}
+
+/// Detects CI from environment variables.
+///
+/// The conventional `CI` variable is checked first, with `GITHUB_ACTIONS` and
+/// `GITLAB_CI` as corroborating signals. A variable counts as set when it is
+/// non-empty and not "false".
+fn is_ci(env: &HashMap<String, String>) -> bool {
+    ["CI", "GITHUB_ACTIONS", "GITLAB_CI"].iter().any(|name| {
+        env.get(*name)
+            .is_some_and(|v| !v.is_empty() && !v.eq_ignore_ascii_case("false"))
+    })
+}
+
+/// Returns `file_path` relative to `workspace_root`.
+///
+/// Relative paths are taken to be relative to the workspace. Both paths are
+/// normalized lexically first, and symlinks are resolved if that is what
+/// puts the file inside the workspace. A file outside the workspace keeps
+/// its absolute path, and an empty `file_path` stays empty.
+pub fn relative_to_workspace(file_path: &str, workspace_root: &str) -> String {
+    if file_path.is_empty() || workspace_root.is_empty() {
+        return file_path.to_string();
+    }
+    let root = normalize_lexically(Path::new(workspace_root));
+    let path = normalize_lexically(&root.join(file_path));
+    let relative = path
+        .strip_prefix(&root)
+        .map(Path::to_path_buf)
+        .or_else(|_| {
+            canonicalize_existing_prefix(&path)
+                .strip_prefix(canonicalize_existing_prefix(&root))
+                .map(Path::to_path_buf)
+        })
+        .map(|relative| relative.to_string_lossy().to_string());
+    match relative {
+        Ok(relative) if !relative.is_empty() => relative,
+        _ => file_path.to_string(),
     }
 }
 
@@ -83,7 +752,78 @@ fn get_current_branch() -> Option<String> {
     }
 }
 
+/// Resolves the login user name from `USER`/`LOGNAME`, then `fallback`.
+///
+/// Returns an empty string if the user cannot be determined.
+fn resolve_user(
+    env: &HashMap<String, String>,
+    fallback: impl FnOnce() -> Option<String>,
+) -> String {
+    ["USER", "LOGNAME"]
+        .iter()
+        .filter_map(|name| env.get(*name))
+        .find(|value| !value.is_empty())
+        .cloned()
+        .or_else(fallback)
+        .unwrap_or_default()
+}
+
+/// Resolves the hostname from `HOSTNAME`, then `fallback`.
+///
+/// Returns an empty string if the hostname cannot be determined.
+fn resolve_hostname(
+    env: &HashMap<String, String>,
+    fallback: impl FnOnce() -> Option<String>,
+) -> String {
+    env.get("HOSTNAME")
+        .filter(|value| !value.is_empty())
+        .cloned()
+        .or_else(fallback)
+        .unwrap_or_default()
+}
+
+fn system_hostname() -> Option<String> {
+    Command::new("hostname")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+}
+
+fn whoami() -> Option<String> {
+    let output = Command::new("whoami").output().ok()?;
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+/// Runs git with the given arguments in `dir` and returns its trimmed stdout.
+///
+/// Returns None if git cannot be run or exits unsuccessfully.
+fn git_output(dir: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new("git");
+    command.args(args);
+    if !dir.is_empty() {
+        command.current_dir(dir);
+    }
+    let output = command.output().ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
     use crate::rule::ToolInput;
@@ -95,8 +835,9 @@ mod tests {
             tool_name: "Bash".to_string(),
             tool_input: ToolInput {
                 command: Some("npm install".to_string()),
-                file_path: None,
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let ctx = Context::from_input(&input);
@@ -113,9 +854,10 @@ mod tests {
         let input = HookInput {
             tool_name: "Read".to_string(),
             tool_input: ToolInput {
-                command: None,
                 file_path: Some("/home/user/project/src/main.rs".to_string()),
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let ctx = Context::from_input(&input);
@@ -133,8 +875,9 @@ mod tests {
             file_path: "/src/main.rs".to_string(),
             file_dir: "/src".to_string(),
             tool_name: "Bash".to_string(),
-            branch: "main".to_string(),
+            branch: OnceCell::from("main".to_string()),
             workspace_root: "/home/user/project".to_string(),
+            ..Default::default()
         };
 
         let result = ctx.expand("Running ${command} on ${branch}");
@@ -147,6 +890,166 @@ mod tests {
         assert_eq!(result, "Dir: /src, Root: /home/user/project");
     }
 
+    #[test]
+    fn test_parse_mcp_tool_name() {
+        assert_eq!(
+            parse_mcp_tool_name("mcp__github__create_issue"),
+            Some(("github", "create_issue"))
+        );
+        assert_eq!(
+            parse_mcp_tool_name("mcp__my_server__list__all"),
+            Some(("my_server", "list__all"))
+        );
+        assert_eq!(parse_mcp_tool_name("Bash"), None);
+        assert_eq!(parse_mcp_tool_name("mcp__github"), None);
+        assert_eq!(parse_mcp_tool_name("mcp____tool"), None);
+    }
+
+    #[test]
+    fn test_expand_mcp_variables() {
+        let input = HookInput {
+            tool_name: "mcp__github__create_issue".to_string(),
+            ..Default::default()
+        };
+        let ctx = Context::from_input(&input);
+        assert_eq!(
+            ctx.expand("${mcp_server}/${mcp_tool}"),
+            "github/create_issue"
+        );
+
+        let ctx = Context::from_input(&HookInput {
+            tool_name: "Bash".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(ctx.expand("[${mcp_server}][${mcp_tool}]"), "[][]");
+    }
+
+    #[test]
+    fn test_expand_os() {
+        let ctx = Context::default();
+        assert_eq!(
+            ctx.expand("on ${os}"),
+            format!("on {}", std::env::consts::OS)
+        );
+    }
+
+    fn env_map(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_ci() {
+        assert!(is_ci(&env_map(&[("CI", "1")])));
+        assert!(is_ci(&env_map(&[("CI", "true")])));
+        assert!(is_ci(&env_map(&[("GITHUB_ACTIONS", "true")])));
+        assert!(is_ci(&env_map(&[("GITLAB_CI", "true")])));
+        assert!(!is_ci(&env_map(&[])));
+        assert!(!is_ci(&env_map(&[("CI", "")])));
+        assert!(!is_ci(&env_map(&[("CI", "false")])));
+        assert!(!is_ci(&env_map(&[("CI", "FALSE"), ("GITHUB_ACTIONS", "")])));
+    }
+
+    #[test]
+    fn test_resolve_user() {
+        let fallback = || Some("fallback".to_string());
+        assert_eq!(
+            resolve_user(&env_map(&[("USER", "alice")]), fallback),
+            "alice"
+        );
+        assert_eq!(
+            resolve_user(&env_map(&[("USER", ""), ("LOGNAME", "bob")]), fallback),
+            "bob"
+        );
+        assert_eq!(resolve_user(&env_map(&[]), fallback), "fallback");
+        assert_eq!(resolve_user(&env_map(&[]), || None), "");
+    }
+
+    #[test]
+    fn test_resolve_hostname() {
+        let fallback = || Some("laptop".to_string());
+        assert_eq!(
+            resolve_hostname(&env_map(&[("HOSTNAME", "prod-jump-1")]), fallback),
+            "prod-jump-1"
+        );
+        assert_eq!(
+            resolve_hostname(&env_map(&[("HOSTNAME", "")]), fallback),
+            "laptop"
+        );
+        assert_eq!(resolve_hostname(&env_map(&[]), || None), "");
+    }
+
+    #[test]
+    fn test_workspace_has() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("bun.lockb"), "").unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("apps/web")).unwrap();
+        std::fs::write(temp_dir.path().join("apps/web/package.json"), "{}").unwrap();
+        let ctx = Context {
+            workspace_root: temp_dir.path().to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let glob = |g: &str| Regex::new(&crate::glob::glob_to_regex(g).unwrap()).unwrap();
+
+        assert!(ctx.workspace_has(&[glob("bun.lockb")], 0));
+        assert!(!ctx.workspace_has(&[glob("package-lock.json")], 0));
+        assert!(!ctx.workspace_has(&[glob("package.json")], 1));
+        assert!(ctx.workspace_has(&[glob("package.json")], 2));
+        assert_eq!(ctx.workspace_entries_cache.borrow().len(), 3);
+    }
+
+    #[test]
+    fn test_transcript_tail() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("transcript.jsonl");
+        std::fs::write(&path, "{\"a\":\"cargo test\"}\n{\"b\":\"done\"}\n").unwrap();
+        let ctx = Context {
+            transcript_path: Some(path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(ctx.transcript_tail(13).unwrap(), "{\"b\":\"done\"}\n");
+        assert!(ctx.transcript_tail(1024).unwrap().contains("cargo test"));
+    }
+
+    #[test]
+    fn test_transcript_tail_missing_file() {
+        let ctx = Context {
+            transcript_path: Some("/nonexistent/cchooked/transcript.jsonl".to_string()),
+            ..Default::default()
+        };
+        assert!(ctx.transcript_tail(1024).is_none());
+        assert!(ctx.transcript_warned.get());
+    }
+
+    #[test]
+    fn test_expand_ci() {
+        let ctx = Context {
+            env: OnceCell::from(env_map(&[("CI", "1")])),
+            ..Default::default()
+        };
+        assert_eq!(ctx.expand("ci=${ci}"), "ci=true");
+
+        let ctx = Context {
+            env: OnceCell::from(env_map(&[])),
+            ..Default::default()
+        };
+        assert_eq!(ctx.expand("ci=${ci}"), "ci=false");
+    }
+
+    #[test]
+    fn test_expand_remote() {
+        let ctx = Context {
+            remote: OnceCell::from("git@github.com:ourorg/app.git".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            ctx.expand("origin: ${remote}"),
+            "origin: git@github.com:ourorg/app.git"
+        );
+    }
+
     #[test]
     fn test_expand_no_variables() {
         let ctx = Context {
@@ -154,8 +1057,9 @@ mod tests {
             file_path: "".to_string(),
             file_dir: "".to_string(),
             tool_name: "Bash".to_string(),
-            branch: "main".to_string(),
+            branch: OnceCell::from("main".to_string()),
             workspace_root: "/home/user/project".to_string(),
+            ..Default::default()
         };
 
         let result = ctx.expand("No variables here");
@@ -169,14 +1073,128 @@ mod tests {
             file_path: "/main.rs".to_string(),
             file_dir: "/".to_string(),
             tool_name: "Read".to_string(),
-            branch: "main".to_string(),
+            branch: OnceCell::from("main".to_string()),
             workspace_root: "/home/user/project".to_string(),
+            ..Default::default()
         };
 
         let result = ctx.expand("${file_dir}");
         assert_eq!(result, "/");
     }
 
+    #[test]
+    fn test_path_exists_resolves_relative_to_workspace_root() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("biome.json"), "{}").unwrap();
+        let ctx = Context {
+            workspace_root: temp_dir.path().to_string_lossy().to_string(),
+            ..Default::default()
+        };
+
+        assert!(ctx.path_exists("biome.json"));
+        assert!(ctx.path_exists("${workspace_root}/biome.json"));
+        assert!(!ctx.path_exists("missing.json"));
+        assert_eq!(ctx.path_exists_cache.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let ctx = Context {
+            command: "echo ${env:SECRET}".to_string(),
+            env: OnceCell::from(env_map(&[("CI_TOKEN_2", "t0k"), ("SECRET", "s3cret")])),
+            ..Default::default()
+        };
+
+        assert_eq!(ctx.expand("--token ${env:CI_TOKEN_2}"), "--token t0k");
+        assert_eq!(ctx.expand("[${env:UNSET}]"), "[]");
+        assert_eq!(ctx.expand("${env:CI_TOKEN_2}/${env:CI_TOKEN_2}"), "t0k/t0k");
+        // 変数名として不正な参照と閉じていない参照はそのまま
+        assert_eq!(ctx.expand("${env:1X} ${env:}"), "${env:1X} ${env:}");
+        assert_eq!(ctx.expand("${env:CI_TOKEN_2"), "${env:CI_TOKEN_2");
+        // 入力から来た値の中の参照は展開しない
+        assert_eq!(ctx.expand("${command}"), "echo ${env:SECRET}");
+    }
+
+    #[test]
+    fn test_expand_groups() {
+        let ctx = Context {
+            values: MatchValues {
+                groups: Some(vec![
+                    "git push origin main".to_string(),
+                    "origin".to_string(),
+                ]),
+                ..Default::default()
+            },
+            command: "echo ${1}".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(ctx.expand("${1} (${0})"), "origin (git push origin main)");
+        // 存在しないグループは空文字列
+        assert_eq!(ctx.expand("[${2}]"), "[]");
+        assert_eq!(ctx.expand("${1x} ${} $1"), "${1x} ${} $1");
+        // 入力から来た値の中の参照は展開しない
+        assert_eq!(ctx.expand("${command}"), "echo ${1}");
+
+        // グループを使わないルールでは展開しない
+        let ctx = Context::default();
+        assert_eq!(ctx.expand("[${1}]"), "[${1}]");
+    }
+
+    #[test]
+    fn test_uses_capture_groups() {
+        assert!(uses_capture_groups("push to ${2}"));
+        assert!(uses_capture_groups("${10}"));
+        assert!(!uses_capture_groups("${command} $1 ${1x} ${env:HOME}"));
+    }
+
+    #[test]
+    fn test_relative_to_workspace_inside() {
+        assert_eq!(
+            relative_to_workspace("/home/user/project/src/main.rs", "/home/user/project"),
+            "src/main.rs"
+        );
+        assert_eq!(
+            relative_to_workspace("/home/user/project/src/main.rs", "/home/user/project/"),
+            "src/main.rs"
+        );
+        assert_eq!(
+            relative_to_workspace("/home/user/project/./src/../lib.rs", "/home/user/project"),
+            "lib.rs"
+        );
+    }
+
+    #[test]
+    fn test_relative_to_workspace_outside() {
+        assert_eq!(
+            relative_to_workspace("/home/user/other/main.rs", "/home/user/project"),
+            "/home/user/other/main.rs"
+        );
+        // 前方一致だがディレクトリ境界ではない
+        assert_eq!(
+            relative_to_workspace("/home/user/project2/main.rs", "/home/user/project"),
+            "/home/user/project2/main.rs"
+        );
+        assert_eq!(
+            relative_to_workspace("/home/user/project/../other/main.rs", "/home/user/project"),
+            "/home/user/project/../other/main.rs"
+        );
+    }
+
+    #[test]
+    fn test_relative_to_workspace_already_relative() {
+        assert_eq!(
+            relative_to_workspace("src/main.rs", "/home/user/project"),
+            "src/main.rs"
+        );
+        assert_eq!(
+            relative_to_workspace("./src/main.rs", "/home/user/project"),
+            "src/main.rs"
+        );
+        assert_eq!(relative_to_workspace("/a/b.rs", ""), "/a/b.rs");
+        assert_eq!(relative_to_workspace("", "/home/user/project"), "");
+    }
+
     #[test]
     #[serial]
     fn test_workspace_root_uses_claude_project_dir() {
@@ -192,8 +1210,9 @@ mod tests {
             tool_name: "Bash".to_string(),
             tool_input: ToolInput {
                 command: Some("test".to_string()),
-                file_path: None,
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let ctx = Context::from_input(&input);
@@ -225,8 +1244,9 @@ mod tests {
             tool_name: "Bash".to_string(),
             tool_input: ToolInput {
                 command: Some("test".to_string()),
-                file_path: None,
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let ctx = Context::from_input(&input);
@@ -246,4 +1266,87 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    #[serial]
+    fn test_workspace_root_fallback_to_input_cwd() {
+        // Save the original value
+        let original = std::env::var("CLAUDE_PROJECT_DIR").ok();
+
+        // SAFETY: This test is serialized to avoid race conditions with other env var tests
+        unsafe {
+            std::env::remove_var("CLAUDE_PROJECT_DIR");
+        }
+
+        let input = HookInput {
+            tool_name: "Bash".to_string(),
+            cwd: Some("/work/payments-service".to_string()),
+            ..Default::default()
+        };
+
+        let ctx = Context::from_input(&input);
+
+        assert_eq!(ctx.workspace_root, "/work/payments-service");
+
+        // Restore the original value
+        // SAFETY: This test is serialized to avoid race conditions with other env var tests
+        unsafe {
+            if let Some(val) = original {
+                std::env::set_var("CLAUDE_PROJECT_DIR", val);
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical_workspace_root_falls_back_when_missing() {
+        let ctx = Context {
+            workspace_root: "/nonexistent/cchooked/workspace".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            ctx.canonical_workspace_root(),
+            "/nonexistent/cchooked/workspace"
+        );
+    }
+
+    #[test]
+    fn test_normalize_lexically() {
+        assert_eq!(
+            normalize_lexically(Path::new("/workspace/../etc/passwd")),
+            PathBuf::from("/etc/passwd")
+        );
+        assert_eq!(
+            normalize_lexically(Path::new("/workspace/./src/../lib.rs")),
+            PathBuf::from("/workspace/lib.rs")
+        );
+        assert_eq!(
+            normalize_lexically(Path::new("/../../etc")),
+            PathBuf::from("/etc")
+        );
+        assert_eq!(
+            normalize_lexically(Path::new("../sibling/x")),
+            PathBuf::from("../sibling/x")
+        );
+    }
+
+    #[test]
+    fn test_outside_workspace() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("project");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let outside = |file_path: &str| {
+            Context {
+                file_path: file_path.to_string(),
+                workspace_root: workspace.to_string_lossy().to_string(),
+                ..Default::default()
+            }
+            .outside_workspace()
+        };
+
+        assert_eq!(outside("src/new/file.rs"), Some(false));
+        assert_eq!(outside("../project-other/file.rs"), Some(true));
+        assert_eq!(outside("src/../../etc/passwd"), Some(true));
+        assert_eq!(outside("/etc/passwd"), Some(true));
+        assert_eq!(outside(""), None);
+    }
 }
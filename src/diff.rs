@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+/// Lines removed and added by a single text edit.
+#[derive(Debug, Default, PartialEq)]
+pub struct LineDiff {
+    /// Lines present in the old text but not in the new text.
+    pub removed: String,
+    /// Lines present in the new text but not in the old text.
+    pub added: String,
+}
+
+/// Computes the effective removed and added text between two strings.
+///
+/// Lines are compared as a multiset after trimming surrounding whitespace, so
+/// pure reorderings and whitespace-only changes produce no removed or added
+/// text. Blank lines are ignored. Reported lines keep their original form and
+/// are joined with `\n` in their original order.
+pub fn line_diff(old: &str, new: &str) -> LineDiff {
+    LineDiff {
+        removed: lines_not_in(old, new),
+        added: lines_not_in(new, old),
+    }
+}
+
+fn lines_not_in(source: &str, other: &str) -> String {
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for line in other.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        *remaining.entry(line).or_insert(0) += 1;
+    }
+
+    let mut result: Vec<&str> = Vec::new();
+    for line in source.lines() {
+        let key = line.trim();
+        if key.is_empty() {
+            continue;
+        }
+        match remaining.get_mut(key) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => result.push(line),
+        }
+    }
+    result.join("\n")
+}
+
+/// Lines of unchanged context shown around each change by `unified_diff`.
+const CONTEXT_LINES: usize = 3;
+
+/// One line of a line-by-line comparison.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineOp<'a> {
+    Keep(&'a str),
+    Remove(&'a str),
+    Add(&'a str),
+}
+
+/// Renders the changes from `old` to `new` as a unified diff with headers
+/// naming `path`. Returns an empty string if the texts have the same lines.
+///
+/// Unlike `line_diff`, lines are compared exactly and in order.
+pub fn unified_diff(old: &str, new: &str, path: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let ops = line_ops(&old, &new);
+
+    // 変更行の前後 CONTEXT_LINES 行を含む範囲をまとめて hunk にする
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        if matches!(op, LineOp::Keep(_)) {
+            continue;
+        }
+        let start = i.saturating_sub(CONTEXT_LINES);
+        let end = (i + CONTEXT_LINES + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {path}\n+++ {path}\n");
+    for (start, end) in hunks {
+        let before = ops.get(..start).unwrap_or_default();
+        let lines = ops.get(start..end).unwrap_or_default();
+        let count = |ops: &[LineOp], old_side: bool| {
+            ops.iter()
+                .filter(|op| match op {
+                    LineOp::Keep(_) => true,
+                    LineOp::Remove(_) => old_side,
+                    LineOp::Add(_) => !old_side,
+                })
+                .count()
+        };
+        let (old_len, new_len) = (count(lines, true), count(lines, false));
+        // 空の範囲の開始行は直前の行番号で表す
+        let old_start = count(before, true) + usize::from(old_len > 0);
+        let new_start = count(before, false) + usize::from(new_len > 0);
+        out.push_str(&format!(
+            "@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"
+        ));
+        for op in lines {
+            let line = match op {
+                LineOp::Keep(line) => format!(" {line}"),
+                LineOp::Remove(line) => format!("-{line}"),
+                LineOp::Add(line) => format!("+{line}"),
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Compares two lists of lines via their longest common subsequence.
+fn line_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<LineOp<'a>> {
+    let width = new.len() + 1;
+    // common[i * width + j] は old[i..] と new[j..] の最長共通部分列の長さ
+    let mut common = vec![0usize; (old.len() + 1) * width];
+    let at = |common: &[usize], i: usize, j: usize| common.get(i * width + j).copied().unwrap_or(0);
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            let value = if old.get(i) == new.get(j) {
+                at(&common, i + 1, j + 1) + 1
+            } else {
+                at(&common, i + 1, j).max(at(&common, i, j + 1))
+            };
+            if let Some(cell) = common.get_mut(i * width + j) {
+                *cell = value;
+            }
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    loop {
+        match (old.get(i), new.get(j)) {
+            (Some(a), Some(b)) if a == b => {
+                ops.push(LineOp::Keep(a));
+                i += 1;
+                j += 1;
+            }
+            (Some(a), Some(_)) if at(&common, i + 1, j) >= at(&common, i, j + 1) => {
+                ops.push(LineOp::Remove(a));
+                i += 1;
+            }
+            (_, Some(b)) => {
+                ops.push(LineOp::Add(b));
+                j += 1;
+            }
+            (Some(a), None) => {
+                ops.push(LineOp::Remove(a));
+                i += 1;
+            }
+            (None, None) => break,
+        }
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_added_line() {
+        let diff = line_diff("a\nb", "a\nconsole.log(x)\nb");
+        assert_eq!(diff.removed, "");
+        assert_eq!(diff.added, "console.log(x)");
+    }
+
+    #[test]
+    fn test_removed_line() {
+        let diff = line_diff("#[test]\n#[serial]\nfn t() {}", "#[test]\nfn t() {}");
+        assert_eq!(diff.removed, "#[serial]");
+        assert_eq!(diff.added, "");
+    }
+
+    #[test]
+    fn test_modified_line() {
+        let diff = line_diff("let x = 1;", "let x = 2;");
+        assert_eq!(diff.removed, "let x = 1;");
+        assert_eq!(diff.added, "let x = 2;");
+    }
+
+    #[test]
+    fn test_pure_reordering_is_not_a_change() {
+        let diff = line_diff("use a;\nuse b;\nuse c;", "use c;\nuse a;\nuse b;");
+        assert_eq!(diff, LineDiff::default());
+    }
+
+    #[test]
+    fn test_whitespace_only_change_is_not_a_change() {
+        let diff = line_diff("fn f() {\nfoo();\n}", "fn f() {\n    foo();  \n}\n\n");
+        assert_eq!(diff, LineDiff::default());
+    }
+
+    #[test]
+    fn test_duplicate_lines_counted() {
+        let diff = line_diff("x\nx\ny", "x\ny");
+        assert_eq!(diff.removed, "x");
+        assert_eq!(diff.added, "");
+    }
+
+    #[test]
+    fn test_reported_lines_keep_indentation() {
+        let diff = line_diff("", "    console.log(x);");
+        assert_eq!(diff.added, "    console.log(x);");
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\n";
+        assert_eq!(
+            unified_diff(old, new, "x.json"),
+            "--- x.json\n+++ x.json\n@@ -2,7 +2,8 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n+i\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_separate_hunks_and_new_file() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+        assert_eq!(
+            unified_diff(old, new, "f"),
+            "--- f\n+++ f\n@@ -1,3 +1,4 @@\n+0\n 1\n 2\n 3\n@@ -7,4 +8,3 @@\n 7\n 8\n 9\n-10\n"
+        );
+        assert_eq!(
+            unified_diff("", "x\n", "f"),
+            "--- f\n+++ f\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+        assert_eq!(unified_diff("same\n", "same", "f"), "");
+    }
+}
@@ -16,6 +16,12 @@ pub enum CchookedError {
         pattern: String,
         detail: String,
     },
+    /// Invalid glob pattern in a rule.
+    GlobError {
+        rule_name: String,
+        pattern: String,
+        detail: String,
+    },
     /// Invalid event type specified.
     InvalidEventType {
         value: String,
@@ -26,10 +32,25 @@ pub enum CchookedError {
         value: String,
         valid: Vec<&'static str>,
     },
+    /// A rule field has an invalid value.
+    InvalidField {
+        rule_name: String,
+        field: &'static str,
+        detail: String,
+    },
     /// Log action specified without a log file path.
     LogFileMissing { rule_name: String },
+    /// `--rule` names a rule that is not defined (or not enabled).
+    UnknownRule {
+        name: String,
+        available: Vec<String>,
+    },
+    /// Nothing was written to stdin within the timeout.
+    StdinTimeout { timeout_ms: u64 },
     /// IO error occurred.
     IoError(io::Error),
+    /// Several errors found while compiling the rules.
+    Multiple(Vec<CchookedError>),
 }
 
 impl fmt::Display for CchookedError {
@@ -54,6 +75,16 @@ impl fmt::Display for CchookedError {
                     "Invalid regex in rule '{rule_name}': pattern '{pattern}' - {detail}"
                 )
             }
+            CchookedError::GlobError {
+                rule_name,
+                pattern,
+                detail,
+            } => {
+                write!(
+                    f,
+                    "Invalid glob in rule '{rule_name}': pattern '{pattern}' - {detail}"
+                )
+            }
             CchookedError::InvalidEventType { value, valid } => {
                 write!(
                     f,
@@ -68,15 +99,46 @@ impl fmt::Display for CchookedError {
                     valid.join(", ")
                 )
             }
+            CchookedError::InvalidField {
+                rule_name,
+                field,
+                detail,
+            } => {
+                write!(f, "Rule '{rule_name}' has invalid '{field}': {detail}")
+            }
             CchookedError::LogFileMissing { rule_name } => {
                 write!(
                     f,
                     "Rule '{rule_name}' uses log action but log_file is not specified"
                 )
             }
+            CchookedError::UnknownRule { name, available } => {
+                if available.is_empty() {
+                    write!(f, "Unknown rule '{name}'. No rules are enabled")
+                } else {
+                    write!(
+                        f,
+                        "Unknown rule '{name}'. Available rules: {}",
+                        available.join(", ")
+                    )
+                }
+            }
+            CchookedError::StdinTimeout { timeout_ms } => {
+                write!(
+                    f,
+                    "No input received on stdin within {timeout_ms}ms. cchooked reads the hook input JSON from stdin (set --stdin-timeout-ms to wait longer, or 0 to wait forever)"
+                )
+            }
             CchookedError::IoError(e) => {
                 write!(f, "IO error: {e}")
             }
+            CchookedError::Multiple(errors) => {
+                write!(f, "{} errors found:", errors.len())?;
+                for error in errors {
+                    write!(f, "\n- {}", error.to_string().replace('\n', "\n  "))?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -101,11 +163,31 @@ impl CchookedError {
             CchookedError::ConfigParseError { .. }
             | CchookedError::InputParseError(_)
             | CchookedError::RegexError { .. }
+            | CchookedError::GlobError { .. }
             | CchookedError::InvalidEventType { .. }
             | CchookedError::InvalidActionType { .. }
+            | CchookedError::InvalidField { .. }
             | CchookedError::LogFileMissing { .. }
+            | CchookedError::UnknownRule { .. }
+            | CchookedError::StdinTimeout { .. }
             | CchookedError::IoError(_) => 2,
+            CchookedError::Multiple(errors) => errors
+                .iter()
+                .map(CchookedError::exit_code)
+                .max()
+                .unwrap_or(2),
+        }
+    }
+
+    /// Combines errors into one: the error itself if there is only one,
+    /// otherwise `Multiple`.
+    pub fn from_errors(mut errors: Vec<CchookedError>) -> Self {
+        if errors.len() == 1
+            && let Some(error) = errors.pop()
+        {
+            return error;
         }
+        CchookedError::Multiple(errors)
     }
 
     /// Returns true if this error should be treated as a warning rather than an error.
@@ -114,6 +196,32 @@ impl CchookedError {
     pub fn is_warning(&self) -> bool {
         matches!(self, CchookedError::ConfigNotFound(_))
     }
+
+    /// Returns true if this error is a problem in the configuration that the
+    /// user has to fix, as opposed to a bad command line, bad hook input or
+    /// an IO failure.
+    pub fn is_config_error(&self) -> bool {
+        match self {
+            CchookedError::ConfigNotFound(_)
+            | CchookedError::ConfigParseError { .. }
+            | CchookedError::RegexError { .. }
+            | CchookedError::GlobError { .. }
+            | CchookedError::InvalidEventType { .. }
+            | CchookedError::InvalidActionType { .. }
+            | CchookedError::InvalidField { .. }
+            | CchookedError::LogFileMissing { .. } => true,
+            CchookedError::UnknownRule { .. }
+            | CchookedError::InputParseError(_)
+            | CchookedError::StdinTimeout { .. }
+            | CchookedError::IoError(_) => false,
+            CchookedError::Multiple(errors) => errors.iter().all(CchookedError::is_config_error),
+        }
+    }
+
+    /// Returns true if this error comes from the command-line arguments.
+    pub fn is_usage_error(&self) -> bool {
+        matches!(self, CchookedError::UnknownRule { .. })
+    }
 }
 
 impl From<io::Error> for CchookedError {
@@ -170,6 +278,17 @@ mod tests {
         assert!(!err.is_warning());
     }
 
+    #[test]
+    fn test_exit_code_glob_error() {
+        let err = CchookedError::GlobError {
+            rule_name: "test".to_string(),
+            pattern: "[invalid".to_string(),
+            detail: "unclosed character class in glob".to_string(),
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert!(!err.is_warning());
+    }
+
     #[test]
     fn test_exit_code_invalid_event_type() {
         let err = CchookedError::InvalidEventType {
@@ -180,6 +299,19 @@ mod tests {
         assert!(!err.is_warning());
     }
 
+    #[test]
+    fn test_unknown_rule_lists_available_rules() {
+        let err = CchookedError::UnknownRule {
+            name: "no-foce-push".to_string(),
+            available: vec!["no-force-push".to_string(), "log-all".to_string()],
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(
+            err.to_string(),
+            "Unknown rule 'no-foce-push'. Available rules: no-force-push, log-all"
+        );
+    }
+
     #[test]
     fn test_exit_code_invalid_action_type() {
         let err = CchookedError::InvalidActionType {
@@ -190,6 +322,21 @@ mod tests {
         assert!(!err.is_warning());
     }
 
+    #[test]
+    fn test_exit_code_invalid_field() {
+        let err = CchookedError::InvalidField {
+            rule_name: "test".to_string(),
+            field: "docs_url",
+            detail: "must not be empty".to_string(),
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert!(!err.is_warning());
+        assert_eq!(
+            err.to_string(),
+            "Rule 'test' has invalid 'docs_url': must not be empty"
+        );
+    }
+
     #[test]
     fn test_exit_code_log_file_missing() {
         let err = CchookedError::LogFileMissing {
@@ -199,6 +346,56 @@ mod tests {
         assert!(!err.is_warning());
     }
 
+    #[test]
+    fn test_multiple_errors() {
+        let err = CchookedError::from_errors(vec![
+            CchookedError::LogFileMissing {
+                rule_name: "a".to_string(),
+            },
+            CchookedError::InvalidField {
+                rule_name: "b".to_string(),
+                field: "docs_url",
+                detail: "must not be empty".to_string(),
+            },
+        ]);
+        assert_eq!(err.exit_code(), 2);
+        assert!(!err.is_warning());
+        assert_eq!(
+            err.to_string(),
+            "2 errors found:\n- Rule 'a' uses log action but log_file is not specified\n- Rule 'b' has invalid 'docs_url': must not be empty"
+        );
+    }
+
+    #[test]
+    fn test_from_errors_single() {
+        let err = CchookedError::from_errors(vec![CchookedError::LogFileMissing {
+            rule_name: "a".to_string(),
+        }]);
+        assert!(matches!(err, CchookedError::LogFileMissing { .. }));
+    }
+
+    #[test]
+    fn test_is_config_error() {
+        assert!(
+            CchookedError::LogFileMissing {
+                rule_name: "a".to_string(),
+            }
+            .is_config_error()
+        );
+        assert!(!CchookedError::StdinTimeout { timeout_ms: 5000 }.is_config_error());
+        assert!(!CchookedError::InputParseError("invalid json".to_string()).is_config_error());
+    }
+
+    #[test]
+    fn test_unknown_rule_is_usage_error() {
+        let err = CchookedError::UnknownRule {
+            name: "no-foce-push".to_string(),
+            available: Vec::new(),
+        };
+        assert!(err.is_usage_error());
+        assert!(!err.is_config_error());
+    }
+
     #[test]
     fn test_exit_code_io_error() {
         let err = CchookedError::IoError(io::Error::new(io::ErrorKind::NotFound, "file not found"));
@@ -0,0 +1,91 @@
+use chrono::Local;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Size of the log above which it is rotated to `errors.log.1`.
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Number of bytes of the hook input kept for an entry.
+const MAX_INPUT_BYTES: usize = 500;
+
+/// `[settings] error_log`; on until a config turns it off, so errors from a
+/// config that fails to load are always logged.
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Beginning of the hook input read from stdin, if any.
+static INPUT: Mutex<String> = Mutex::new(String::new());
+
+/// Sets whether errors are logged from `[settings] error_log`.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Keeps the first bytes of the hook input so that they can be logged
+/// with an error.
+pub fn record_input(input: &str) {
+    let mut end = input.len().min(MAX_INPUT_BYTES);
+    while !input.is_char_boundary(end) {
+        end -= 1;
+    }
+    if let Ok(mut recorded) = INPUT.lock() {
+        *recorded = input.get(..end).unwrap_or_default().to_string();
+    }
+}
+
+/// Logs panics as well, before the default panic message is printed.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        write_entry(&info.to_string());
+        default_hook(info);
+    }));
+}
+
+/// Appends an entry for an error that ended the run to the error log:
+/// `$XDG_STATE_HOME/cchooked/errors.log`, or `~/.cache/cchooked/errors.log`.
+///
+/// This is best effort: a log that cannot be written is silently skipped,
+/// so logging never changes the outcome of the run.
+pub fn write_entry(error: &str) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+    let Some(path) = log_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent()
+        && fs::create_dir_all(dir).is_err()
+    {
+        return;
+    }
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() >= MAX_LOG_BYTES) {
+        let _ = fs::rename(&path, path.with_extension("log.1"));
+    }
+
+    let argv: Vec<String> = std::env::args().collect();
+    let input = INPUT.lock().map(|input| input.clone()).unwrap_or_default();
+    let line = format!(
+        "[{}] cchooked {} argv={argv:?} error={error:?} stdin={input:?}",
+        Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
+        env!("CARGO_PKG_VERSION"),
+    );
+    let _ = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{line}"));
+}
+
+fn log_path() -> Option<PathBuf> {
+    let non_empty = |name: &str| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let dir = non_empty("XDG_STATE_HOME")
+        .or_else(|| non_empty("HOME").map(|home| home.join(".cache")))?;
+    Some(dir.join("cchooked").join("errors.log"))
+}
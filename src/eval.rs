@@ -0,0 +1,98 @@
+use crate::action::{self, DisableMode};
+use crate::context::Context;
+use crate::output::{self, Output};
+use crate::rule::{self, ActionType, EventType, HookInput, Rule};
+
+/// Evaluates the rules against a synthesized input and describes the
+/// outcome: the matched rules, the decision, and the exit code the hook
+/// would return.
+///
+/// Block actions have no side effects and are always evaluated. Run and log
+/// actions are only described unless `execute` is set, in which case they
+/// are executed as the hook would. Exits with 0, whatever the decision.
+pub fn run_eval(
+    event: &EventType,
+    input: &HookInput,
+    rules: &[Rule],
+    context: Context,
+    execute: bool,
+) -> Output {
+    let mut report = vec![format!(
+        "Event: {}, tool: {}",
+        event.as_str(),
+        input.tool_name
+    )];
+    let mut hook_output = output::no_match_output();
+    let mut skipped = false;
+
+    match rule::evaluate_rules(rules, event, input, context) {
+        Some(result) => {
+            for shadow in &result.shadow_results {
+                report.push(format!(
+                    "{} by rule '{}' (dry_run rule, not applied)",
+                    shadow.action.as_str(),
+                    shadow.rule_name
+                ));
+            }
+            for matched in result.log_results.iter().chain(&result.terminal_result) {
+                let runs = execute || matched.action == ActionType::Block;
+                let note = match matched.action {
+                    ActionType::Block => "",
+                    _ if execute => " (executed)",
+                    _ => " (not executed, pass --execute to run it)",
+                };
+                report.push(format!(
+                    "{} by rule '{}'{note}",
+                    matched.action.as_str(),
+                    matched.rule_name
+                ));
+                for line in action::describe_action(matched, &result.context) {
+                    report.push(format!("  {}", line.replace('\n', "\n    ")));
+                }
+
+                let terminal = matched.action != ActionType::Log;
+                if runs {
+                    let out = action::execute_action(
+                        matched,
+                        &result.context,
+                        event,
+                        DisableMode::Enabled,
+                        false,
+                    );
+                    if terminal {
+                        hook_output = out;
+                    }
+                } else if terminal {
+                    skipped = true;
+                }
+            }
+        }
+        None => report.push("No rule matched.".to_string()),
+    }
+
+    let decision = if hook_output.blocked {
+        "block"
+    } else {
+        "allow"
+    };
+    report.push(format!("Decision: {decision}"));
+    if let Some(stdout) = &hook_output.stdout {
+        report.push(format!("Hook stdout: {}", stdout.trim_end()));
+    }
+    if let Some(stderr) = &hook_output.stderr {
+        report.push(format!("Hook stderr: {}", stderr.replace('\n', "\n  ")));
+    }
+    let note = if skipped {
+        " (the run command was not executed)"
+    } else {
+        ""
+    };
+    report.push(format!("Exit code: {}{note}", hook_output.exit_code));
+
+    Output {
+        exit_code: 0,
+        stdout: Some(report.join("\n") + "\n"),
+        stderr: None,
+        blocked: false,
+    }
+}
@@ -0,0 +1,104 @@
+use crate::action;
+use crate::context::Context;
+use crate::output::Output;
+use crate::rule::{self, ActionType, EventType, HookInput, Rule, RuleTrace};
+
+/// Evaluates the rules against an input and prints why each rule did or did
+/// not match, followed by the actions that would run.
+///
+/// Nothing is executed: run commands, working directories, log files and
+/// block messages are only shown with their templates expanded. Always
+/// exits with 0, whatever the decision.
+pub fn run_explain(
+    event: &EventType,
+    input: &HookInput,
+    rules: &[Rule],
+    context: Context,
+) -> Output {
+    let (result, trace) = rule::trace_rules(rules, event, input, context);
+
+    let mut report = vec![format!(
+        "Event: {}, tool: {}",
+        event.as_str(),
+        input.tool_name
+    )];
+    if rules.is_empty() {
+        report.push("No rules are configured.".to_string());
+    }
+    report.extend(trace_lines(rules, &trace, input));
+
+    report.push(String::new());
+    let mut blocked = false;
+    match &result {
+        Some(result) => {
+            report.push("Actions (not executed):".to_string());
+            for shadow in &result.shadow_results {
+                report.push(format!(
+                    "  {} by rule '{}' (dry_run rule, not applied)",
+                    shadow.action.as_str(),
+                    shadow.rule_name
+                ));
+            }
+            for matched in result.log_results.iter().chain(&result.terminal_result) {
+                blocked |= matched.action == ActionType::Block;
+                report.push(format!(
+                    "  {} by rule '{}'",
+                    matched.action.as_str(),
+                    matched.rule_name
+                ));
+                for line in action::describe_action(matched, &result.context) {
+                    report.push(format!("    {}", line.replace('\n', "\n      ")));
+                }
+            }
+        }
+        None => report.push("No rule matched.".to_string()),
+    }
+    report.push(format!(
+        "Decision: {}",
+        if blocked { "block" } else { "allow" }
+    ));
+
+    Output {
+        exit_code: 0,
+        stdout: Some(report.join("\n") + "\n"),
+        stderr: None,
+        blocked: false,
+    }
+}
+
+/// Formats one line per rule (one per failed condition) saying whether it
+/// matched and, if not, why it was skipped.
+pub fn trace_lines(
+    rules: &[Rule],
+    trace: &[(String, RuleTrace)],
+    input: &HookInput,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (rule, (name, outcome)) in rules.iter().zip(trace) {
+        match outcome {
+            RuleTrace::WrongEvent => lines.push(format!(
+                "skipped      {name}: rule is for {}",
+                rule.event.as_str()
+            )),
+            RuleTrace::MatcherMismatch => lines.push(format!(
+                "skipped      {name}: matcher did not match {:?}",
+                input.tool_name
+            )),
+            RuleTrace::ConditionsFailed(reasons) => {
+                for reason in reasons {
+                    lines.push(format!("skipped      {name}: {reason}"));
+                }
+            }
+            RuleTrace::Matched => {
+                lines.push(format!("matched      {name} ({})", rule.action.as_str()))
+            }
+            RuleTrace::Bypassed(env) => lines.push(format!(
+                "bypassed     {name}: allowed by ${env} (allow_if_env)"
+            )),
+            RuleTrace::NotReached => lines.push(format!(
+                "not reached  {name}: evaluation stopped at an earlier rule"
+            )),
+        }
+    }
+    lines
+}
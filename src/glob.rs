@@ -0,0 +1,184 @@
+/// Translates a gitignore-style glob pattern into an equivalent regex pattern.
+///
+/// Supported syntax:
+/// - `*` matches any sequence of characters except `/`
+/// - `?` matches a single character except `/`
+/// - `**` matches across directories (`**/` also matches zero directories)
+/// - `[...]` / `[!...]` character classes
+/// - `\` escapes the next character
+///
+/// Patterns starting with `/` are anchored at the start of the path; other
+/// patterns match at any directory boundary. A trailing `/` matches everything
+/// under that directory.
+pub fn glob_to_regex(glob: &str) -> Result<String, String> {
+    if glob.is_empty() {
+        return Err("empty glob pattern".to_string());
+    }
+
+    let (anchored, body) = match glob.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, glob),
+    };
+
+    let mut regex = String::from(if anchored { "^/" } else { "(?:^|/)" });
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        regex.push_str("(?:.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                } else {
+                    regex.push_str("[^/]*");
+                }
+            }
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if matches!(chars.peek(), Some('!') | Some('^')) {
+                    chars.next();
+                    regex.push('^');
+                }
+                let mut first = true;
+                let mut closed = false;
+                while let Some(class_char) = chars.next() {
+                    match class_char {
+                        ']' if !first => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' => match chars.next() {
+                            Some(escaped) => push_class_char(&mut regex, escaped),
+                            None => return Err("dangling escape in glob".to_string()),
+                        },
+                        '-' => regex.push('-'),
+                        other => push_class_char(&mut regex, other),
+                    }
+                    first = false;
+                }
+                if !closed {
+                    return Err("unclosed character class in glob".to_string());
+                }
+                regex.push(']');
+            }
+            '\\' => match chars.next() {
+                Some(escaped) => regex.push_str(&regex_lite::escape(&escaped.to_string())),
+                None => return Err("dangling escape in glob".to_string()),
+            },
+            other => regex.push_str(&regex_lite::escape(&other.to_string())),
+        }
+    }
+
+    if body.ends_with('/') {
+        regex.push_str(".*");
+    }
+    regex.push('$');
+
+    Ok(regex)
+}
+
+fn push_class_char(regex: &mut String, c: char) {
+    if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~' | '-') {
+        regex.push('\\');
+    }
+    regex.push(c);
+}
+
+/// Normalizes Windows-style path separators to `/` for glob matching.
+pub fn normalize_separators(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use regex_lite::Regex;
+
+    fn glob_matches(glob: &str, path: &str) -> bool {
+        let regex = Regex::new(&glob_to_regex(glob).unwrap()).unwrap();
+        regex.is_match(&normalize_separators(path))
+    }
+
+    #[test]
+    fn test_star_does_not_cross_directories() {
+        assert!(glob_matches("*.rs", "/src/main.rs"));
+        assert!(glob_matches("src/*.rs", "/home/user/src/main.rs"));
+        assert!(!glob_matches("src/*.rs", "/home/user/src/bin/main.rs"));
+    }
+
+    #[test]
+    fn test_double_star() {
+        assert!(glob_matches("**/*.test.ts", "/app/src/a/b/c.test.ts"));
+        assert!(glob_matches("**/*.test.ts", "c.test.ts"));
+        assert!(glob_matches(
+            "src/**/generated/*",
+            "/repo/src/generated/api.ts"
+        ));
+        assert!(glob_matches(
+            "src/**/generated/*",
+            "/repo/src/a/b/generated/api.ts"
+        ));
+        assert!(!glob_matches(
+            "src/**/generated/*",
+            "/repo/src/generated/x/api.ts"
+        ));
+        assert!(glob_matches("dist/**", "/repo/dist/a/b.js"));
+    }
+
+    #[test]
+    fn test_dot_is_literal() {
+        assert!(glob_matches("*.env", "/app/.env"));
+        assert!(!glob_matches("a.b", "/app/axb"));
+    }
+
+    #[test]
+    fn test_question_mark_and_class() {
+        assert!(glob_matches("file?.txt", "/tmp/file1.txt"));
+        assert!(!glob_matches("file?.txt", "/tmp/file/.txt"));
+        assert!(glob_matches("[ab].rs", "/src/a.rs"));
+        assert!(!glob_matches("[ab].rs", "/src/c.rs"));
+        assert!(glob_matches("[!ab].rs", "/src/c.rs"));
+        assert!(glob_matches("v[0-9].md", "/docs/v2.md"));
+    }
+
+    #[test]
+    fn test_anchored_pattern() {
+        assert!(glob_matches("/etc/*", "/etc/passwd"));
+        assert!(!glob_matches("/etc/*", "/home/etc/passwd"));
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        assert!(glob_matches("src/*.rs", "src/main.rs"));
+        assert!(!glob_matches("src/*.rs", "mysrc/main.rs"));
+    }
+
+    #[test]
+    fn test_trailing_slash_matches_directory_contents() {
+        assert!(glob_matches(
+            "node_modules/",
+            "/app/node_modules/pkg/index.js"
+        ));
+        assert!(!glob_matches("node_modules/", "/app/node_modules_backup"));
+    }
+
+    #[test]
+    fn test_windows_separators_normalized() {
+        assert!(glob_matches("src/**/*.rs", r"C:\repo\src\bin\main.rs"));
+        assert!(glob_matches("*.rs", r"C:\repo\main.rs"));
+    }
+
+    #[test]
+    fn test_invalid_globs() {
+        assert!(glob_to_regex("[abc").is_err());
+        assert!(glob_to_regex("abc\\").is_err());
+        assert!(glob_to_regex("").is_err());
+    }
+}
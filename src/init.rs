@@ -0,0 +1,97 @@
+use crate::output::Output;
+use std::fs;
+use std::path::Path;
+
+/// Config written by `cchooked init`. Every rule is disabled, so the file
+/// changes nothing until the user turns a rule on.
+const STARTER_CONFIG: &str = r#"# cchooked のルール設定
+#
+# 詳しい書き方: https://github.com/1gy/cchooked/blob/main/docs/usage.md
+# 変更したら `cchooked check` でルールを確認できます。
+# 以下のルールはすべて無効化されています。使うルールの `enabled = false` を削除してください。
+
+schema = 1
+
+# コマンドをブロックする例: npm の代わりに bun を使うよう促す
+[rules.prefer-bun]
+event = "PreToolUse"
+matcher = "Bash"
+action = "block"
+enabled = false
+message = "npm ではなく bun を使用してください: ${command}"
+when.command = "^npm\\s"
+
+# 編集後にコマンドを実行する例: Rust ファイルを保存したら整形する
+[rules.format-rust]
+event = "PostToolUse"
+matcher = "Edit|Write"
+action = "run"
+enabled = false
+command = "rustfmt ${file_path}"
+when.file_path = "\\.rs$"
+
+# ログを記録する例: 実行されたコマンドを .claude/commands.log に追記する
+# （priority を上げて、ブロックされるコマンドも先に記録する）
+[rules.log-commands]
+event = "PreToolUse"
+matcher = "Bash"
+action = "log"
+enabled = false
+priority = 10
+log_file = ".claude/commands.log"
+"#;
+
+/// Hook settings that make Claude Code call cchooked.
+const SETTINGS_SNIPPET: &str = r#"{
+  "hooks": {
+    "PreToolUse": [
+      {
+        "matcher": "Bash|Edit|Write",
+        "hooks": [{ "type": "command", "command": "cchooked PreToolUse" }]
+      }
+    ],
+    "PostToolUse": [
+      {
+        "matcher": "Edit|Write",
+        "hooks": [{ "type": "command", "command": "cchooked PostToolUse" }]
+      }
+    ]
+  }
+}"#;
+
+/// Writes a starter config to `path` and prints the settings.json snippet
+/// that wires cchooked up.
+///
+/// Missing parent directories are created. An existing file is only
+/// overwritten if `force` is set; otherwise nothing is written and the exit
+/// code is 1.
+pub fn run_init(path: &Path, force: bool) -> crate::error::Result<Output> {
+    if path.exists() && !force {
+        return Ok(Output {
+            exit_code: 1,
+            stdout: None,
+            stderr: Some(format!(
+                "Error: {} already exists (use --force to overwrite)",
+                path.display()
+            )),
+            blocked: false,
+        });
+    }
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, STARTER_CONFIG)?;
+
+    Ok(Output {
+        exit_code: 0,
+        stdout: Some(format!(
+            "Created {}\n\nAdd the following to .claude/settings.json to run cchooked:\n\n{SETTINGS_SNIPPET}\n",
+            path.display()
+        )),
+        stderr: None,
+        blocked: false,
+    })
+}
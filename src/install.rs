@@ -0,0 +1,254 @@
+use crate::diff;
+use crate::output::Output;
+use crate::rule::EventType;
+use serde_json::{Map, Value, json};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Settings file that `cchooked install` edits by default.
+pub const DEFAULT_SETTINGS_PATH: &str = ".claude/settings.json";
+
+/// Settings file edited with `--local` (not meant to be committed).
+pub const LOCAL_SETTINGS_PATH: &str = ".claude/settings.local.json";
+
+/// Events wired up when `--events` is not given.
+pub const DEFAULT_EVENTS: &str = "PreToolUse,PostToolUse";
+
+/// What `cchooked install` did for one event.
+#[derive(Debug, PartialEq)]
+enum Change {
+    /// A new hook entry was added.
+    Added,
+    /// An existing cchooked hook was changed to the new command (holds the old one).
+    Updated(String),
+    /// The hook was already installed.
+    Unchanged,
+}
+
+/// Adds a hook running `<program> <EVENT>` for each event to the settings
+/// file at `path`, creating the file if it does not exist.
+///
+/// A hook that already runs cchooked for the event is updated in place
+/// instead of being added again, so running this twice changes nothing.
+/// Only its program is replaced; arguments such as `--config` are kept.
+/// Other keys and hooks are kept. With `dry_run`, the change is printed as
+/// a diff instead of being written.
+pub fn run_install(
+    path: &Path,
+    events: &[EventType],
+    program: &str,
+    dry_run: bool,
+) -> crate::error::Result<Output> {
+    let old = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut settings = if old.trim().is_empty() {
+        Map::new()
+    } else {
+        match serde_json::from_str(&old) {
+            Ok(Value::Object(settings)) => settings,
+            Ok(_) => return Ok(failure(format!("{} is not a JSON object", path.display()))),
+            Err(e) => return Ok(failure(format!("failed to parse {}: {e}", path.display()))),
+        }
+    };
+
+    let changes = match install_hooks(&mut settings, events, program) {
+        Ok(changes) => changes,
+        Err(e) => return Ok(failure(format!("{}: {e}", path.display()))),
+    };
+    let changed = old.trim().is_empty()
+        || changes
+            .iter()
+            .any(|(_, change)| *change != Change::Unchanged);
+    let new = if changed {
+        serde_json::to_string_pretty(&settings)? + "\n"
+    } else {
+        old.clone()
+    };
+
+    let mut report = Vec::new();
+    if dry_run {
+        let diff = diff::unified_diff(&old, &new, &path.display().to_string());
+        if diff.is_empty() {
+            report.push(format!("No changes to {}", path.display()));
+        } else {
+            report.push(diff.trim_end().to_string());
+        }
+    } else {
+        for (command, change) in &changes {
+            report.push(match change {
+                Change::Added => format!("Added hook: {command}"),
+                Change::Updated(previous) => {
+                    format!("Updated hook: {command} (was: {previous})")
+                }
+                Change::Unchanged => format!("Already installed: {command}"),
+            });
+        }
+        if changed {
+            if let Some(parent) = path.parent()
+                && !parent.as_os_str().is_empty()
+            {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, &new)?;
+            report.push(format!("Wrote {}", path.display()));
+        } else {
+            report.push(format!("No changes to {}", path.display()));
+        }
+    }
+
+    Ok(Output {
+        exit_code: 0,
+        stdout: Some(report.join("\n") + "\n"),
+        stderr: None,
+        blocked: false,
+    })
+}
+
+fn failure(message: String) -> Output {
+    Output {
+        exit_code: 1,
+        stdout: None,
+        stderr: Some(format!("Error: {message}")),
+        blocked: false,
+    }
+}
+
+/// Adds or updates the cchooked hook of each event in `settings`, returning
+/// each hook's command and what was done to it.
+fn install_hooks(
+    settings: &mut Map<String, Value>,
+    events: &[EventType],
+    program: &str,
+) -> Result<Vec<(String, Change)>, String> {
+    let hooks = settings
+        .entry("hooks")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or("\"hooks\" is not an object")?;
+
+    let mut changes = Vec::new();
+    for event in events {
+        let groups = hooks
+            .entry(event.as_str())
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or(format!("\"hooks.{}\" is not an array", event.as_str()))?;
+
+        let existing = groups
+            .iter_mut()
+            .filter_map(|group| group.get_mut("hooks")?.as_array_mut())
+            .flatten()
+            .filter_map(|hook| hook.get_mut("command"))
+            .find(|existing| {
+                existing
+                    .as_str()
+                    .is_some_and(|existing| invokes_cchooked(existing, event))
+            });
+        let (command, change) = match existing {
+            Some(existing) => {
+                let previous = existing.as_str().unwrap_or_default().to_string();
+                let arguments = cchooked_arguments(&previous, event).unwrap_or(event.as_str());
+                let command = format!("{program} {arguments}");
+                if command == previous {
+                    (command, Change::Unchanged)
+                } else {
+                    *existing = Value::String(command.clone());
+                    (command, Change::Updated(previous))
+                }
+            }
+            None => {
+                let command = format!("{program} {}", event.as_str());
+                groups.push(json!({
+                    "matcher": "*",
+                    "hooks": [{ "type": "command", "command": command }],
+                }));
+                (command, Change::Added)
+            }
+        };
+        changes.push((command, change));
+    }
+    Ok(changes)
+}
+
+/// Returns true if a hook command runs cchooked (by any path) for `event`.
+pub fn invokes_cchooked(command: &str, event: &EventType) -> bool {
+    cchooked_arguments(command, event).is_some()
+}
+
+/// Returns the arguments of a hook command that runs cchooked (by any
+/// path) for `event`: everything after the program, starting with the event.
+fn cchooked_arguments<'a>(command: &'a str, event: &EventType) -> Option<&'a str> {
+    let (program, _) = command.split_once(&format!(" {}", event.as_str()))?;
+    let is_cchooked = Path::new(program.trim().trim_matches(['"', '\'']))
+        .file_stem()
+        .is_some_and(|stem| stem == "cchooked");
+    is_cchooked.then(|| command.get(program.len() + 1..))?
+}
+
+/// Returns how hooks should invoke the running binary: `cchooked` if that
+/// name on PATH is this binary, otherwise its full path.
+pub fn program_name() -> String {
+    let Ok(exe) = env::current_exe().and_then(fs::canonicalize) else {
+        return "cchooked".to_string();
+    };
+    let name = format!("cchooked{}", env::consts::EXE_SUFFIX);
+    let on_path = env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths)
+            .filter_map(|dir| fs::canonicalize(dir.join(&name)).ok())
+            .any(|found| found == exe)
+    });
+    if on_path {
+        return "cchooked".to_string();
+    }
+
+    let path = exe.to_string_lossy();
+    if path
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._-:\\".contains(c))
+    {
+        path.into_owned()
+    } else {
+        format!("'{}'", path.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invokes_cchooked() {
+        let pre = EventType::PreToolUse;
+        assert!(invokes_cchooked("cchooked PreToolUse", &pre));
+        assert!(invokes_cchooked(
+            "/usr/local/bin/cchooked PreToolUse --config x.toml",
+            &pre
+        ));
+        assert!(invokes_cchooked(
+            "'/opt/my tools/cchooked' PreToolUse",
+            &pre
+        ));
+        assert!(!invokes_cchooked("cchooked PostToolUse", &pre));
+        assert!(!invokes_cchooked("other-hook PreToolUse", &pre));
+        assert!(!invokes_cchooked("echo cchooked", &pre));
+    }
+
+    #[test]
+    fn test_cchooked_arguments() {
+        let pre = EventType::PreToolUse;
+        assert_eq!(
+            cchooked_arguments("/usr/local/bin/cchooked PreToolUse --config x.toml", &pre),
+            Some("PreToolUse --config x.toml")
+        );
+        assert_eq!(
+            cchooked_arguments("cchooked PreToolUse", &pre),
+            Some("PreToolUse")
+        );
+        assert_eq!(cchooked_arguments("other-hook PreToolUse", &pre), None);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::config::Config;
+use crate::output::{self, Output};
+use crate::rule::{self, EventType, Rule, ToolMatcher};
+use serde_json::{Map, Value, json};
+
+/// Output format of `cchooked list`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListFormat {
+    /// Aligned columns for reading in a terminal.
+    Table,
+    /// A JSON array for tooling.
+    Json,
+}
+
+impl ListFormat {
+    /// Parses a `--format` value.
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "table" => Some(ListFormat::Table),
+            "json" => Some(ListFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A rule as shown by `cchooked list`.
+struct Entry {
+    rule: Rule,
+    enabled: bool,
+    when: Map<String, Value>,
+}
+
+/// Lists the compiled rules in evaluation order, followed by the disabled
+/// rules. Only rules for `event` are listed if it is given.
+pub fn run_list(
+    config: &Config,
+    rules: Vec<Rule>,
+    event: Option<&EventType>,
+    format: ListFormat,
+) -> Output {
+    let mut disabled: Vec<Rule> = config
+        .rules
+        .iter()
+        .filter(|(name, rule)| {
+            rule.enabled == Some(false)
+                && rule.is_abstract != Some(true)
+                && !rules.iter().any(|compiled| compiled.name == **name)
+        })
+        .filter_map(|(name, _)| rule::compile_named(name, config).ok())
+        .collect();
+    rule::sort_rules(&mut disabled, config);
+
+    let entries: Vec<Entry> = rules
+        .into_iter()
+        .map(|rule| (rule, true))
+        .chain(disabled.into_iter().map(|rule| (rule, false)))
+        .filter(|(rule, _)| event.is_none_or(|event| rule.event == *event))
+        .map(|(rule, enabled)| Entry {
+            when: when_conditions(&rule.name, config),
+            rule,
+            enabled,
+        })
+        .collect();
+
+    let stdout = match format {
+        ListFormat::Table => table(&entries),
+        ListFormat::Json => {
+            let values: Vec<Value> = entries.iter().map(entry_json).collect();
+            serde_json::to_string_pretty(&values).unwrap_or_default() + "\n"
+        }
+    };
+    Output {
+        exit_code: 0,
+        stdout: Some(stdout),
+        stderr: None,
+        blocked: false,
+    }
+}
+
+/// Returns the `when` conditions a rule sets (after `extends` is resolved).
+fn when_conditions(name: &str, config: &Config) -> Map<String, Value> {
+    let Some(rule_config) = config.rules.get(name) else {
+        return Map::new();
+    };
+    let when = match rule_config.extends {
+        Some(_) => rule::resolve_extends(name, &config.rules)
+            .ok()
+            .and_then(|resolved| serde_json::to_value(resolved.when).ok()),
+        None => serde_json::to_value(&rule_config.when).ok(),
+    };
+    match when {
+        Some(Value::Object(conditions)) => conditions
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .collect(),
+        _ => Map::new(),
+    }
+}
+
+fn matcher_json(matcher: &ToolMatcher) -> Value {
+    match matcher {
+        ToolMatcher::Any => Value::Null,
+        ToolMatcher::Regex(regex) => json!(regex.as_str()),
+        ToolMatcher::Names { names, .. } => json!(names),
+    }
+}
+
+fn entry_json(entry: &Entry) -> Value {
+    let rule = &entry.rule;
+    json!({
+        "name": rule.name,
+        "priority": rule.priority,
+        "event": rule.event.as_str(),
+        "matcher": matcher_json(&rule.matcher),
+        "action": rule.action.as_str(),
+        "enabled": entry.enabled,
+        "dry_run": rule.dry_run,
+        "tags": rule.tags,
+        "when": entry.when,
+    })
+}
+
+/// Renders a condition value compactly: strings as-is, string lists
+/// comma-separated, anything else as JSON.
+fn condition_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(Value::is_string) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => value.to_string(),
+    }
+}
+
+fn table(entries: &[Entry]) -> String {
+    let header = ["NAME", "PRIORITY", "EVENT", "MATCHER", "ACTION", "WHEN"].map(str::to_string);
+    let rows = entries.iter().map(|entry| {
+        let rule = &entry.rule;
+        let name = if !entry.enabled {
+            format!("{} (disabled)", rule.name)
+        } else if rule.dry_run {
+            format!("{} (dry run)", rule.name)
+        } else {
+            rule.name.clone()
+        };
+        let matcher = match &rule.matcher {
+            ToolMatcher::Any => "*".to_string(),
+            ToolMatcher::Regex(regex) => regex.as_str().to_string(),
+            ToolMatcher::Names { names, .. } => names.join(","),
+        };
+        let when = entry
+            .when
+            .iter()
+            .map(|(key, value)| format!("{key}={}", condition_text(value)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        [
+            name,
+            rule.priority.to_string(),
+            rule.event.as_str().to_string(),
+            matcher,
+            rule.action.as_str().to_string(),
+            when,
+        ]
+    });
+    output::table(std::iter::once(header).chain(rows))
+}
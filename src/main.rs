@@ -1,128 +1,249 @@
 mod action;
+mod batch;
+mod binary;
+mod cache;
+mod check;
+mod cli;
+mod completions;
 mod config;
 mod context;
+mod diff;
 mod error;
+mod errorlog;
+mod eval;
+mod explain;
+mod glob;
+mod init;
+mod install;
+mod list;
+mod migrate;
 mod output;
 mod parser;
+mod permission;
+mod replay;
+mod report;
 mod rule;
+mod selftest;
+mod stdin;
+mod style;
+mod verbose;
+mod version;
+mod watchdog;
 
+use cli::{Args, Command};
 use error::CchookedError;
-use rule::{EventType, HookInput, ToolInput};
+use rule::{EventType, HookInput, TextEdit, ToolInput};
 use serde::Deserialize;
+use std::cell::OnceCell;
 use std::env;
-use std::io::{self, Read};
+use std::path::Path;
+use std::time::Instant;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-struct Args {
-    event: Option<String>,
-    config_path: Option<String>,
-    show_help: bool,
-    show_version: bool,
-}
-
 #[derive(Debug, Deserialize)]
 struct RawHookInput {
+    #[serde(default)]
     tool_name: String,
+    #[serde(default)]
     tool_input: RawToolInput,
+    cwd: Option<String>,
+    permission_mode: Option<String>,
+    transcript_path: Option<String>,
+    model: Option<serde_json::Value>,
+    agent: Option<serde_json::Value>,
+    tool_response: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct RawToolInput {
     command: Option<String>,
     file_path: Option<String>,
+    content: Option<String>,
+    old_string: Option<String>,
+    new_string: Option<String>,
+    edits: Option<Vec<RawEdit>>,
+    subagent_type: Option<String>,
+    prompt: Option<String>,
+    description: Option<String>,
     #[serde(flatten)]
     _extra: serde_json::Value, // Ignore other fields
 }
 
+#[derive(Debug, Deserialize)]
+struct RawEdit {
+    #[serde(default)]
+    old_string: String,
+    #[serde(default)]
+    new_string: String,
+}
+
+/// Extracts the exit code from a tool_response, if it reports one.
+fn exit_code_from_response(response: &serde_json::Value) -> Option<i32> {
+    ["exit_code", "exitCode"]
+        .iter()
+        .find_map(|key| response.get(key)?.as_i64())
+        .and_then(|code| i32::try_from(code).ok())
+}
+
+/// Extracts a model or agent identity from a string or an object with an
+/// `id`, `name`, or `display_name` field.
+fn identity_from_value(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Object(_) => ["id", "name", "display_name"]
+            .iter()
+            .find_map(|key| value.get(key)?.as_str())
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
 impl From<RawHookInput> for HookInput {
     fn from(raw: RawHookInput) -> Self {
+        // Edit は単一の old_string/new_string、MultiEdit は edits 配列を持つ
+        let mut edits = Vec::new();
+        if raw.tool_input.old_string.is_some() || raw.tool_input.new_string.is_some() {
+            edits.push(TextEdit {
+                old_string: raw.tool_input.old_string.unwrap_or_default(),
+                new_string: raw.tool_input.new_string.unwrap_or_default(),
+            });
+        }
+        edits.extend(
+            raw.tool_input
+                .edits
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| TextEdit {
+                    old_string: e.old_string,
+                    new_string: e.new_string,
+                }),
+        );
+
         HookInput {
             tool_name: raw.tool_name,
             tool_input: ToolInput {
                 command: raw.tool_input.command,
                 file_path: raw.tool_input.file_path,
+                content: raw.tool_input.content,
+                edits,
+                subagent_type: raw.tool_input.subagent_type,
+                prompt: raw.tool_input.prompt,
+                description: raw.tool_input.description,
             },
+            cwd: raw.cwd,
+            permission_mode: raw.permission_mode,
+            transcript_path: raw.transcript_path,
+            model: raw
+                .model
+                .as_ref()
+                .or(raw.agent.as_ref())
+                .and_then(identity_from_value),
+            tool_exit_code: raw.tool_response.as_ref().and_then(exit_code_from_response),
+            raw_tool_input: serde_json::Value::Null,
         }
     }
 }
 
-fn print_help() {
-    eprintln!(
-        r#"cchooked - Claude Code Hooks Engine
-
-USAGE:
-    cchooked <EVENT> [OPTIONS]
+fn print_version() {
+    eprintln!("cchooked {VERSION}");
+}
 
-ARGUMENTS:
-    <EVENT>    Event type: PreToolUse or PostToolUse
+/// Reads the hook input JSON from stdin, giving up after the stdin timeout.
+fn read_input(args: &Args) -> error::Result<HookInput> {
+    parse_input(read_input_value(args)?)
+}
 
-OPTIONS:
-    --config <PATH>    Path to config file (default: .claude/hooks-rules.toml)
-    --help, -h         Show this help message
-    --version, -v      Show version
+/// Reads the hook input JSON from stdin without interpreting it.
+fn read_input_value(args: &Args) -> error::Result<serde_json::Value> {
+    let buffer = stdin::read_to_string(stdin_timeout_ms(args))?;
+    errorlog::record_input(&buffer);
+    Ok(serde_json::from_str(&buffer)?)
+}
 
-EXAMPLES:
-    echo '{{"tool_name":"Bash","tool_input":{{"command":"npm install"}}}}' | cchooked PreToolUse
-    cchooked PreToolUse --config /path/to/hooks-rules.toml < input.json"#
-    );
+fn stdin_timeout_ms(args: &Args) -> u64 {
+    args.stdin_timeout_ms.unwrap_or(stdin::DEFAULT_TIMEOUT_MS)
 }
 
-fn print_version() {
-    eprintln!("cchooked {VERSION}");
+/// Converts a hook input JSON value into a `HookInput`.
+fn parse_input(value: serde_json::Value) -> error::Result<HookInput> {
+    let raw_tool_input = value.get("tool_input").cloned().unwrap_or_default();
+    let raw: RawHookInput = serde_json::from_value(value)?;
+    let mut input = HookInput::from(raw);
+    input.raw_tool_input = raw_tool_input;
+    Ok(input)
 }
 
-fn parse_args() -> Args {
-    let args: Vec<String> = env::args().skip(1).collect();
-    let mut result = Args {
-        event: None,
-        config_path: None,
-        show_help: false,
-        show_version: false,
-    };
+/// Loads the config and applies the selected profiles.
+fn load_config(args: &Args) -> error::Result<config::Config> {
+    let mut config = config::load_config(args.config_path.as_deref())?;
+    let profiles = args
+        .profile
+        .clone()
+        .or_else(|| env::var("CCHOOKED_PROFILE").ok())
+        .unwrap_or_default();
+    let profiles: Vec<&str> = profiles
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    config.apply_profiles(&profiles);
+    style::set_choice(config.settings.color.as_deref());
+    errorlog::set_enabled(config.settings.error_log.unwrap_or(true));
+    Ok(config)
+}
 
-    let mut i = 0;
-    while i < args.len() {
-        if let Some(arg) = args.get(i) {
-            match arg.as_str() {
-                "--help" | "-h" => result.show_help = true,
-                "--version" | "-v" => result.show_version = true,
-                "--config" => {
-                    i += 1;
-                    if let Some(config_arg) = args.get(i) {
-                        result.config_path = Some(config_arg.clone());
-                    } else {
-                        eprintln!("Error: --config option requires a value");
-                        std::process::exit(2);
-                    }
-                }
-                a if !a.starts_with('-') && result.event.is_none() => {
-                    result.event = Some(a.to_string());
-                }
-                a if a.starts_with('-') => {
-                    eprintln!("Warning: unknown argument '{a}'");
-                }
-                _ => {}
-            }
+/// Loads the config and compiles the rules that pass the tag filter and,
+/// if `--rule` is given, have one of the given names.
+fn load_rules(args: &Args) -> error::Result<(config::Config, Vec<rule::Rule>)> {
+    let config = load_config(args)?;
+    let mut rules = rule::compile_rules(&config)?;
+    let tag_filter = rule::TagFilter::new(
+        args.only_tags
+            .clone()
+            .or_else(|| env::var("CCHOOKED_ONLY_TAGS").ok())
+            .as_deref(),
+        args.skip_tags
+            .clone()
+            .or_else(|| env::var("CCHOOKED_SKIP_TAGS").ok())
+            .as_deref(),
+    );
+    rules.retain(|rule| tag_filter.allows(&rule.tags));
+    if !args.rules.is_empty() {
+        if let Some(name) = args
+            .rules
+            .iter()
+            .find(|name| !rules.iter().any(|rule| &rule.name == *name))
+        {
+            return Err(CchookedError::UnknownRule {
+                name: name.clone(),
+                available: rules.iter().map(|rule| rule.name.clone()).collect(),
+            });
         }
-        i += 1;
+        rules.retain(|rule| args.rules.contains(&rule.name));
     }
-
-    result
+    Ok((config, rules))
 }
 
-fn read_input() -> error::Result<HookInput> {
-    let mut buffer = String::new();
-    io::stdin().read_to_string(&mut buffer)?;
-    let raw: RawHookInput = serde_json::from_str(&buffer)?;
-    Ok(raw.into())
+/// Creates the execution context for an input under the loaded config.
+fn build_context(input: &HookInput, config: &config::Config) -> context::Context {
+    let mut context = context::Context::from_input(input);
+    context.vars = config.vars.clone();
+    if let Some(project_dir) = &config.project_dir {
+        context.set_project_dir(project_dir);
+    }
+    context
 }
 
 fn run() -> error::Result<output::Output> {
-    let args = parse_args();
+    let raw_args: Vec<String> = env::args().skip(1).collect();
+    let args = match cli::parse(&raw_args) {
+        Ok(args) => args,
+        Err(e) => return Ok(e.into_output()),
+    };
 
     if args.show_help {
-        print_help();
+        eprint!("{}", cli::help(args.command));
         return Ok(output::no_match_output());
     }
 
@@ -131,36 +252,312 @@ fn run() -> error::Result<output::Output> {
         return Ok(output::no_match_output());
     }
 
-    let event_str = args.event.ok_or_else(|| {
+    match args.command {
+        Command::Hook if args.check_examples => {
+            let (_, rules) = load_rules(&args)?;
+            Ok(selftest::run_examples(&rules))
+        }
+        Command::Hook => run_hook(&args),
+        Command::Test => run_test(&args),
+        Command::Check => Ok(match load_config(&args) {
+            Ok(config) => check::run_check(&config),
+            Err(e) => check::config_error(&e),
+        }),
+        Command::List => run_list(&args),
+        Command::Explain => {
+            let event = required_event(&args, "cchooked explain <EVENT>")?;
+            let input = read_input(&args)?;
+            let (config, rules) = load_rules(&args)?;
+            let context = build_context(&input, &config);
+            Ok(explain::run_explain(&event, &input, &rules, context))
+        }
+        Command::Eval => run_eval(&args),
+        Command::Batch => {
+            let event = required_event(&args, "cchooked batch <EVENT>")?;
+            let inputs = stdin::read_to_string(stdin_timeout_ms(&args))?;
+            let (config, rules) = load_rules(&args)?;
+            Ok(batch::run_batch(&event, &inputs, &config, &rules))
+        }
+        Command::Replay => {
+            let audit_log = args.audit_log.as_deref().ok_or_else(|| {
+                CchookedError::InputParseError(
+                    "Missing --audit-log option. Usage: cchooked replay --audit-log <PATH>"
+                        .to_string(),
+                )
+            })?;
+            let (config, rules) = load_rules(&args)?;
+            replay::run_replay(Path::new(audit_log), &config, &rules, args.fail_on_change)
+        }
+        Command::Init => {
+            let path = args
+                .config_path
+                .as_deref()
+                .unwrap_or(config::DEFAULT_CONFIG_PATH);
+            init::run_init(Path::new(path), args.force)
+        }
+        Command::Install => run_install(&args),
+        Command::MigrateFromSettings => {
+            let settings = args
+                .settings_path
+                .as_deref()
+                .unwrap_or(install::DEFAULT_SETTINGS_PATH);
+            let config = args
+                .config_path
+                .as_deref()
+                .unwrap_or(config::DEFAULT_CONFIG_PATH);
+            migrate::run_migrate(Path::new(settings), Path::new(config), args.write)
+        }
+        Command::Completions => Ok(completions::run_completions(args.argument.as_deref())),
+        Command::CompleteRules => {
+            let (_, rules) = load_rules(&args)?;
+            let names: String = rules
+                .iter()
+                .map(|rule| format!("{}\n", rule.name))
+                .collect();
+            Ok(output::Output {
+                exit_code: 0,
+                stdout: Some(names),
+                stderr: None,
+                blocked: false,
+            })
+        }
+    }
+}
+
+/// Returns the `<EVENT>` argument, or an error showing `usage` if it is missing.
+fn required_event(args: &Args, usage: &str) -> error::Result<EventType> {
+    let event = args.argument.as_deref().ok_or_else(|| {
+        CchookedError::InputParseError(format!("Missing event argument. Usage: {usage}"))
+    })?;
+    EventType::from_str(event)
+}
+
+fn run_test(args: &Args) -> error::Result<output::Output> {
+    let (config, rules) = load_rules(args)?;
+    let Some(fixtures) = &args.fixtures else {
+        return Ok(selftest::run_tests(&config, &rules));
+    };
+    let event = required_event(args, "cchooked test <EVENT> --fixtures <DIR>")?;
+    selftest::run_fixtures(
+        &event,
+        Path::new(fixtures),
+        args.expect_file.as_deref().map(Path::new),
+        &config,
+        &rules,
+    )
+}
+
+fn run_list(args: &Args) -> error::Result<output::Output> {
+    let format = args.format.as_deref().unwrap_or("table");
+    let Some(format) = list::ListFormat::from_str(format) else {
+        return Ok(output::Output {
+            exit_code: 2,
+            stdout: None,
+            stderr: Some(format!(
+                "Error: --format must be table or json, got '{format}'"
+            )),
+            blocked: false,
+        });
+    };
+    let event = args
+        .event_option
+        .as_deref()
+        .map(EventType::from_str)
+        .transpose()?;
+    let (config, rules) = load_rules(args)?;
+    Ok(list::run_list(&config, rules, event.as_ref(), format))
+}
+
+fn run_eval(args: &Args) -> error::Result<output::Output> {
+    let event = args.event_option.as_deref().ok_or_else(|| {
         CchookedError::InputParseError(
-            "Missing event argument. Usage: cchooked <EVENT>".to_string(),
+            "Missing --event option. Usage: cchooked eval --event <EVENT> --tool <TOOL> --command <CMD>"
+                .to_string(),
         )
     })?;
+    let event = EventType::from_str(event)?;
+    let mut tool_input = serde_json::Map::new();
+    if let Some(command) = &args.command_line {
+        tool_input.insert("command".to_string(), command.clone().into());
+    }
+    if let Some(file_path) = &args.file_path {
+        tool_input.insert("file_path".to_string(), file_path.clone().into());
+    }
+    let input = parse_input(serde_json::json!({
+        "tool_name": args.tool.as_deref().unwrap_or("Bash"),
+        "tool_input": tool_input,
+    }))?;
+    let (config, rules) = load_rules(args)?;
+    let mut context = build_context(&input, &config);
+    if let Some(branch) = &args.branch {
+        context.branch = OnceCell::from(branch.clone());
+    }
+    Ok(eval::run_eval(
+        &event,
+        &input,
+        &rules,
+        context,
+        args.execute,
+    ))
+}
 
-    let event = EventType::from_str(&event_str)?;
-    let input = read_input()?;
-    let config = config::load_config(args.config_path.as_deref())?;
-    let rules = rule::compile_rules(&config)?;
+fn run_install(args: &Args) -> error::Result<output::Output> {
+    let default_path = if args.local {
+        install::LOCAL_SETTINGS_PATH
+    } else {
+        install::DEFAULT_SETTINGS_PATH
+    };
+    let path = args.settings_path.as_deref().unwrap_or(default_path);
+    let events = args
+        .install_events
+        .as_deref()
+        .unwrap_or(install::DEFAULT_EVENTS)
+        .split(',')
+        .map(str::trim)
+        .filter(|event| !event.is_empty())
+        .map(EventType::from_str)
+        .collect::<error::Result<Vec<_>>>()?;
+    install::run_install(
+        Path::new(path),
+        &events,
+        &install::program_name(),
+        args.dry_run,
+    )
+}
 
-    match rule::evaluate_rules(&rules, &event, &input) {
-        Some(eval_result) => {
-            for log_result in &eval_result.log_results {
-                action::execute_action(log_result, &eval_result.context, &event);
+/// Evaluates the rules for the hook input on stdin and executes the actions.
+fn run_hook(args: &Args) -> error::Result<output::Output> {
+    let disable_mode = action::DisableMode::from_env();
+    if disable_mode == action::DisableMode::All {
+        // 親プロセスへの broken pipe を避けるため入力は読み捨てる
+        let _ = stdin::read_to_string(stdin_timeout_ms(args));
+        eprintln!("cchooked disabled via {}", action::DISABLE_ENV);
+        return Ok(output::no_match_output());
+    }
+
+    let report_only = action::report_only_from_env();
+    if report_only {
+        eprintln!(
+            "cchooked: report-only mode via {}: blocks are reported but not enforced",
+            action::REPORT_ONLY_ENV
+        );
+    }
+
+    let started = Instant::now();
+    let env_max_runtime = watchdog::max_runtime_from_env();
+    if let Some(max_runtime_ms) = env_max_runtime {
+        watchdog::start(started, max_runtime_ms);
+    }
+    let mut verbose = verbose::Verbose::new(args.verbose);
+    let event = required_event(args, "cchooked <EVENT>")?;
+    let report_path = args.report_file.as_deref().map(Path::new);
+    let reporting = match args.report.as_deref() {
+        None => report_path.is_some(),
+        Some("json") => true,
+        Some(format) => {
+            return Ok(output::Output {
+                exit_code: 2,
+                stdout: None,
+                stderr: Some(format!("Error: --report must be json, got '{format}'")),
+                blocked: false,
+            });
+        }
+    };
+    let input_value = read_input_value(args)?;
+    // レポートには受け取った入力をそのまま残す（cchooked replay で再評価するため）
+    let raw_input = reporting.then(|| input_value.clone());
+    let input = parse_input(input_value)?;
+    verbose.phase("reading input");
+    let (config, rules) = load_rules(args)?;
+    watchdog::set_policy(config.settings.timeout_policy.as_deref());
+    if env_max_runtime.is_none()
+        && let Some(max_runtime_ms) = config.settings.max_runtime_ms
+    {
+        watchdog::start(started, max_runtime_ms);
+    }
+    verbose.config(&config, &rules);
+    verbose.phase("loading config");
+    let context = build_context(&input, &config);
+
+    let (result, report) = if verbose.enabled() || reporting {
+        let (result, trace) = rule::trace_rules(&rules, &event, &input, context);
+        verbose.evaluation(&event, &input, &rules, &trace, result.as_ref());
+        let report = reporting.then(|| {
+            report::Report::new(
+                &config,
+                &event,
+                &input,
+                raw_input.as_ref(),
+                &rules,
+                &trace,
+                result.as_ref(),
+            )
+        });
+        (result, report)
+    } else {
+        (rule::evaluate_rules(&rules, &event, &input, context), None)
+    };
+    verbose.phase("evaluating rules");
+
+    let mut out = if args.dry_run {
+        let json = match args.format.as_deref().unwrap_or("text") {
+            "text" => false,
+            "json" => true,
+            format => {
+                return Ok(output::Output {
+                    exit_code: 2,
+                    stdout: None,
+                    stderr: Some(format!(
+                        "Error: --format must be text or json, got '{format}'"
+                    )),
+                    blocked: false,
+                });
             }
-            match eval_result.terminal_result {
-                Some(ref terminal_result) => Ok(action::execute_action(
-                    terminal_result,
-                    &eval_result.context,
-                    &event,
-                )),
-                None => Ok(output::no_match_output()),
+        };
+        action::dry_run_output(result.as_ref(), &event, disable_mode, report_only, json)
+    } else {
+        let out = match result {
+            Some(eval_result) => {
+                for (rule, env) in &eval_result.context.bypassed {
+                    eprintln!("cchooked: rule '{rule}' bypassed via {env}");
+                }
+                for shadow_result in &eval_result.shadow_results {
+                    action::record_dry_run(shadow_result, &eval_result.context, &event);
+                }
+                for log_result in &eval_result.log_results {
+                    action::execute_action(
+                        log_result,
+                        &eval_result.context,
+                        &event,
+                        disable_mode,
+                        report_only,
+                    );
+                }
+                match eval_result.terminal_result {
+                    Some(ref terminal_result) => action::execute_action(
+                        terminal_result,
+                        &eval_result.context,
+                        &event,
+                        disable_mode,
+                        report_only,
+                    ),
+                    None => output::no_match_output(),
+                }
             }
-        }
-        None => Ok(output::no_match_output()),
+            None => output::no_match_output(),
+        };
+        verbose.phase("executing actions");
+        verbose.log(&format!("exit code: {}", out.exit_code));
+        out
+    };
+    if let Some(report) = report {
+        report.emit(&mut out, args.dry_run, started.elapsed(), report_path);
     }
+    Ok(out)
 }
 
 fn main() {
+    errorlog::install_panic_hook();
     let result = run();
 
     match result {
@@ -170,13 +567,19 @@ fn main() {
         }
         Err(e) => {
             if e.is_warning() {
-                eprintln!("Warning: {e}");
+                eprintln!("{}", style::warning(&format!("Warning: {e}")));
                 let out = output::no_match_output();
                 output::emit(&out);
             } else {
-                eprintln!(
-                    "[cchooked] Configuration error (user action required - do not auto-fix):\n{e}"
-                );
+                errorlog::write_entry(&e.to_string());
+                let heading = if e.is_usage_error() {
+                    "[cchooked] Usage error"
+                } else if e.is_config_error() {
+                    "[cchooked] Configuration error (user action required - do not auto-fix)"
+                } else {
+                    "[cchooked] Error"
+                };
+                eprintln!("{}", style::error(&format!("{heading}:\n{e}")));
             }
             std::process::exit(e.exit_code());
         }
@@ -0,0 +1,581 @@
+use crate::install;
+use crate::output::Output;
+use crate::parser;
+use crate::rule::EventType;
+use regex_lite::Regex;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A command hook found in a Claude Code settings file.
+struct SettingsHook {
+    event: String,
+    matcher: String,
+    command: String,
+}
+
+/// The input field a migrated hook inspects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Command,
+    FilePath,
+}
+
+/// What a `jq` call outputs.
+enum JqOutput {
+    /// The whole hook input (`.`).
+    Input,
+    /// One field of `tool_input`.
+    Field(Field),
+}
+
+/// One command of a hook, with the separator that follows it.
+struct Segment {
+    words: Vec<String>,
+    /// `&&`, `||`, `;` or `|`; `None` for the last command.
+    separator: Option<&'static str>,
+    /// Whether the command follows a `then`.
+    then: bool,
+    /// Whether the command follows a `then` or a `{`, so that a `;` after
+    /// it stays inside the group.
+    grouped: bool,
+}
+
+/// What a recognized hook does, expressed as a cchooked rule.
+#[derive(Debug, PartialEq)]
+enum Migrated {
+    /// Greps a field and exits with 2 when it matches.
+    Block {
+        field: Field,
+        pattern: String,
+        message: Option<String>,
+    },
+    /// Appends the input (or one field of it) to a file.
+    Log { file: String, json: bool },
+}
+
+/// Converts the command hooks of a settings file into rules.
+///
+/// Hooks of recognizable shapes (a `jq | grep` check that exits with 2, or
+/// a `jq`/`cat` pipeline appending to a file) become `[rules.*]` tables,
+/// printed as TOML or appended to `config_path` when `write` is set. Hooks
+/// that already run cchooked are skipped; any other hook is listed as
+/// needing manual migration.
+pub fn run_migrate(
+    settings_path: &Path,
+    config_path: &Path,
+    write: bool,
+) -> crate::error::Result<Output> {
+    let content = fs::read_to_string(settings_path)?;
+    let settings: Value = match serde_json::from_str(&content) {
+        Ok(settings) => settings,
+        Err(e) => {
+            return Ok(Output {
+                exit_code: 1,
+                stdout: None,
+                stderr: Some(format!(
+                    "Error: failed to parse {}: {e}",
+                    settings_path.display()
+                )),
+                blocked: false,
+            });
+        }
+    };
+
+    let mut taken = if write {
+        existing_rule_names(config_path)?
+    } else {
+        HashSet::new()
+    };
+    let mut rules = Vec::new();
+    let mut manual = Vec::new();
+    for hook in settings_hooks(&settings) {
+        let event = EventType::from_str(&hook.event).ok();
+        if event
+            .as_ref()
+            .is_some_and(|event| install::invokes_cchooked(&hook.command, event))
+        {
+            continue;
+        }
+        match event.and_then(|_| recognize(&hook.command)) {
+            Some(migrated) => {
+                let name = (1..)
+                    .map(|n| format!("migrated-{n}"))
+                    .find(|name| !taken.contains(name))
+                    .unwrap_or_default();
+                rules.push(rule_toml(&name, &hook, &migrated));
+                taken.insert(name);
+            }
+            None => manual.push(format!(
+                "needs manual migration ({}, matcher {:?}): {}",
+                hook.event, hook.matcher, hook.command
+            )),
+        }
+    }
+
+    let stdout = if write {
+        if !rules.is_empty() {
+            append_rules(config_path, &rules)?;
+        }
+        let plural = if rules.len() == 1 { "" } else { "s" };
+        let mut lines = vec![format!(
+            "Appended {} rule{plural} to {}",
+            rules.len(),
+            config_path.display()
+        )];
+        lines.extend(manual);
+        lines.join("\n") + "\n"
+    } else {
+        let mut sections = vec![format!(
+            "# Rules migrated from {} by cchooked migrate-from-settings.\n\
+             # Remove the migrated hooks from the settings file once these rules are in place.",
+            settings_path.display()
+        )];
+        sections.extend(rules);
+        if !manual.is_empty() {
+            sections.push(
+                manual
+                    .iter()
+                    .map(|line| format!("# {line}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        sections.join("\n\n") + "\n"
+    };
+    Ok(Output {
+        exit_code: 0,
+        stdout: Some(stdout),
+        stderr: None,
+        blocked: false,
+    })
+}
+
+/// Lists the command hooks of every event in settings order.
+fn settings_hooks(settings: &Value) -> Vec<SettingsHook> {
+    let Some(events) = settings.get("hooks").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let mut hooks = Vec::new();
+    for (event, groups) in events {
+        for group in groups.as_array().into_iter().flatten() {
+            let matcher = group.get("matcher").and_then(Value::as_str).unwrap_or("");
+            let commands = group
+                .get("hooks")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|hook| hook.get("type").and_then(Value::as_str) == Some("command"))
+                .filter_map(|hook| hook.get("command").and_then(Value::as_str));
+            for command in commands {
+                hooks.push(SettingsHook {
+                    event: event.clone(),
+                    matcher: matcher.to_string(),
+                    command: command.to_string(),
+                });
+            }
+        }
+    }
+    hooks
+}
+
+/// Recognizes a hook command, returning `None` for anything it is not sure of.
+fn recognize(command: &str) -> Option<Migrated> {
+    // if / then / { } で囲まれた形も、中のコマンドだけを見れば同じ形になる
+    let segments: Vec<Segment> = parser::try_split_with_separators(command)?
+        .into_iter()
+        .map(|(mut words, separator)| {
+            let keywords = words
+                .iter()
+                .take_while(|word| matches!(word.as_str(), "if" | "then" | "{"))
+                .count();
+            let opened: Vec<String> = words.drain(..keywords).collect();
+            Segment {
+                words,
+                separator,
+                then: opened.iter().any(|word| word == "then"),
+                grouped: opened.iter().any(|word| word == "then" || word == "{"),
+            }
+        })
+        .filter(|segment| {
+            !segment.words.is_empty()
+                && !matches!(segment.words.as_slice(), [w] if w == "fi" || w == "}")
+        })
+        .collect();
+    recognize_block(&segments).or_else(|| recognize_log(&segments))
+}
+
+fn recognize_block(segments: &[Segment]) -> Option<Migrated> {
+    let mut field = None;
+    let mut pattern = None;
+    let mut message = None;
+    let mut exits = false;
+    for (index, segment) in segments.iter().enumerate() {
+        let (program, args) = segment.words.split_first()?;
+        let separator = segment.separator;
+        // grep が失敗したときに exit しないのは && か if / then でつないだ形だけ
+        let guards_next =
+            separator == Some("&&") || segments.get(index + 1).is_some_and(|next| next.then);
+        match program.as_str() {
+            "jq" if field.is_none() && separator == Some("|") => match jq_output(args)? {
+                JqOutput::Field(jq_field) => field = Some(jq_field),
+                JqOutput::Input => return None,
+            },
+            "grep" if pattern.is_none() && guards_next => pattern = Some(grep_pattern(args)?),
+            "echo"
+                if message.is_none()
+                    && (separator == Some("&&") || segment.grouped && separator == Some(";")) =>
+            {
+                message = Some(echo_message(args)?)
+            }
+            "exit" if args == ["2"] => exits = true,
+            _ => return None,
+        }
+    }
+    if !exits {
+        return None;
+    }
+    Some(Migrated::Block {
+        field: field?,
+        pattern: pattern?,
+        message,
+    })
+}
+
+fn recognize_log(segments: &[Segment]) -> Option<Migrated> {
+    let (source, rest) = segments.split_first()?;
+    if !rest.is_empty() && source.separator != Some("|") {
+        return None;
+    }
+    let (program, args) = source.words.split_first()?;
+    let (args, appended) = match args {
+        [args @ .., redirect, file] if redirect == ">>" => (args, Some(file)),
+        _ => (args, None),
+    };
+    let json = match program.as_str() {
+        "cat" if args.is_empty() => true,
+        "jq" => matches!(jq_output(args)?, JqOutput::Input),
+        _ => return None,
+    };
+    let file = match (appended, rest) {
+        (Some(file), []) => file,
+        (None, [tee]) => match tee.words.as_slice() {
+            [tee, append, file] if tee == "tee" && append == "-a" => file,
+            [tee, append, file, null] if tee == "tee" && append == "-a" && null == ">/dev/null" => {
+                file
+            }
+            [tee, append, file, redirect, null]
+                if tee == "tee" && append == "-a" && redirect == ">" && null == "/dev/null" =>
+            {
+                file
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(Migrated::Log {
+        file: file.clone(),
+        json,
+    })
+}
+
+/// Parses the arguments of a `jq` call that outputs one field of
+/// `tool_input` or the whole input.
+fn jq_output(args: &[String]) -> Option<JqOutput> {
+    let mut filter = None;
+    for arg in args {
+        match arg.as_str() {
+            "-r" | "-c" | "-e" | "--raw-output" | "--compact-output" => {}
+            _ if filter.is_none() => filter = Some(arg.trim()),
+            _ => return None,
+        }
+    }
+    let filter = filter?;
+    if filter == "." {
+        return Some(JqOutput::Input);
+    }
+    let rest = filter.strip_prefix(".tool_input.")?;
+    let (name, fallback) = match rest.split_once("//") {
+        Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+        None => (rest, None),
+    };
+    if fallback.is_some_and(|fallback| !matches!(fallback, "empty" | "\"\"")) {
+        return None;
+    }
+    match name {
+        "command" => Some(JqOutput::Field(Field::Command)),
+        "file_path" => Some(JqOutput::Field(Field::FilePath)),
+        _ => None,
+    }
+}
+
+/// Converts the arguments of a quiet `grep` into a regex.
+///
+/// Basic regular expressions are only accepted if they mean the same as an
+/// extended one, and the result must compile.
+fn grep_pattern(args: &[String]) -> Option<String> {
+    let mut patterns = Vec::new();
+    let (mut quiet, mut extended, mut fixed, mut ignore_case, mut whole_line) =
+        (false, false, false, false, false);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-e" {
+            patterns.push(args.next()?.clone());
+        } else if let Some(flags) = arg.strip_prefix('-').filter(|flags| !flags.is_empty()) {
+            for flag in flags.chars() {
+                match flag {
+                    'q' => quiet = true,
+                    'E' | 'P' => extended = true,
+                    'F' => fixed = true,
+                    'i' => ignore_case = true,
+                    'x' => whole_line = true,
+                    _ => return None,
+                }
+            }
+        } else {
+            patterns.push(arg.clone());
+        }
+    }
+    if !quiet || patterns.is_empty() {
+        return None;
+    }
+
+    let mut converted = Vec::new();
+    for pattern in patterns {
+        if fixed {
+            converted.push(regex_lite::escape(&pattern));
+        } else if extended {
+            converted.push(pattern);
+        } else if pattern.contains('\\') {
+            return None;
+        } else {
+            // BRE では + ? | ( ) { } は通常の文字
+            converted.push(pattern.chars().fold(String::new(), |mut out, c| {
+                if "+?|(){}".contains(c) {
+                    out.push('\\');
+                }
+                out.push(c);
+                out
+            }));
+        }
+    }
+    let mut regex = converted.join("|");
+    if whole_line {
+        regex = format!("^(?:{regex})$");
+    }
+    if ignore_case {
+        regex = format!("(?i){regex}");
+    }
+    Regex::new(&regex).ok()?;
+    Some(regex)
+}
+
+/// Returns the message of an `echo` to stderr. Messages using shell
+/// expansions are not recognized.
+fn echo_message(args: &[String]) -> Option<String> {
+    let (redirect, words) = args.split_last()?;
+    if !matches!(redirect.as_str(), ">&2" | "1>&2") || words.iter().any(|w| w.contains('$')) {
+        return None;
+    }
+    Some(words.join(" "))
+}
+
+/// Renders a rule for a recognized hook.
+fn rule_toml(name: &str, hook: &SettingsHook, migrated: &Migrated) -> String {
+    let mut lines = vec![
+        format!("# {}", hook.command.replace('\n', " ")),
+        format!("[rules.{name}]"),
+        format!("event = {}", toml_string(&hook.event)),
+    ];
+    if !matches!(hook.matcher.as_str(), "" | "*") {
+        lines.push(format!("matcher = {}", toml_string(&hook.matcher)));
+    }
+    match migrated {
+        Migrated::Block {
+            field,
+            pattern,
+            message,
+        } => {
+            lines.push("action = \"block\"".to_string());
+            if let Some(message) = message {
+                lines.push(format!("message = {}", toml_string(message)));
+            }
+            match field {
+                Field::Command => {
+                    // grep はコマンド全体を検索する
+                    lines.push("match_segments = false".to_string());
+                    lines.push(format!("when.command = {}", toml_string(pattern)));
+                }
+                Field::FilePath => lines.push(format!("when.file_path = {}", toml_string(pattern))),
+            }
+        }
+        Migrated::Log { file, json } => {
+            lines.push("action = \"log\"".to_string());
+            lines.push(format!("log_file = {}", toml_string(file)));
+            let format = if *json { "json" } else { "text" };
+            lines.push(format!("log_format = \"{format}\""));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Quotes a TOML string, as a literal string if it contains backslashes so
+/// that regexes stay readable.
+fn toml_string(value: &str) -> String {
+    let plain = !value.chars().any(char::is_control);
+    if plain && !value.contains(['"', '\\']) {
+        return format!("\"{value}\"");
+    }
+    if plain && !value.contains('\'') {
+        return format!("'{value}'");
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", u32::from(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Returns the names of the `[rules.*]` tables already in a config file.
+fn existing_rule_names(path: &Path) -> crate::error::Result<HashSet<String>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let table: toml::Table =
+        toml::from_str(&content).map_err(|e| crate::error::CchookedError::ConfigParseError {
+            path: path.display().to_string(),
+            detail: e.to_string(),
+        })?;
+    Ok(table
+        .get("rules")
+        .and_then(toml::Value::as_table)
+        .map(|rules| rules.keys().cloned().collect())
+        .unwrap_or_default())
+}
+
+/// Appends rules to a config file, creating it (and its directory) if needed.
+fn append_rules(path: &Path, rules: &[String]) -> crate::error::Result<()> {
+    let mut content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    if !content.is_empty() {
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+    content.push_str(&rules.join("\n\n"));
+    content.push('\n');
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(field: Field, pattern: &str, message: Option<&str>) -> Option<Migrated> {
+        Some(Migrated::Block {
+            field,
+            pattern: pattern.to_string(),
+            message: message.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn test_recognize_grep_block() {
+        assert_eq!(
+            recognize(
+                "jq -r '.tool_input.command' | grep -qE '^rm -rf' && echo 'No rm -rf' >&2 && exit 2"
+            ),
+            block(Field::Command, "^rm -rf", Some("No rm -rf"))
+        );
+        assert_eq!(
+            recognize(
+                "if jq -r .tool_input.file_path | grep -q '.env'; then echo \"env files are off limits\" >&2; exit 2; fi"
+            ),
+            block(Field::FilePath, ".env", Some("env files are off limits"))
+        );
+        assert_eq!(
+            recognize(
+                "jq -r .tool_input.command | grep -q '^npm' && { echo 'use bun' >&2; exit 2; }"
+            ),
+            block(Field::Command, "^npm", Some("use bun"))
+        );
+        assert_eq!(
+            recognize("jq -r '.tool_input.command' | grep -qiF 'a+b' && exit 2"),
+            block(Field::Command, r"(?i)a\+b", None)
+        );
+        // BRE の ( ) はリテラル
+        assert_eq!(
+            recognize("jq -r '.tool_input.command' | grep -q 'f(x)' && exit 2"),
+            block(Field::Command, r"f\(x\)", None)
+        );
+    }
+
+    #[test]
+    fn test_recognize_tee_log() {
+        assert_eq!(
+            recognize("jq -c . >> ~/.claude/bash.log"),
+            Some(Migrated::Log {
+                file: "~/.claude/bash.log".to_string(),
+                json: true,
+            })
+        );
+        assert_eq!(
+            recognize("jq -r '.tool_input.command' | tee -a commands.log > /dev/null"),
+            Some(Migrated::Log {
+                file: "commands.log".to_string(),
+                json: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_recognize_rejects_unknown_shapes() {
+        for command in [
+            "./scripts/check.sh",
+            "jq -r '.tool_input.command' | grep -qv '^ls' && exit 2",
+            "jq -r '.tool_input.command' | grep -q 'rm' && echo \"blocked $CMD\" >&2 && exit 2",
+            "jq -r '.tool_input.command' | grep -q 'rm' && curl -X POST example.com && exit 2",
+            "jq -r '.tool_input.command' | grep -q 'rm'",
+            "jq -r '.tool_input.content' | grep -q 'TODO' && exit 2",
+            "jq -c . | tee commands.log",
+            "jq -r '.tool_input.command' | grep -q 'rm' || exit 2",
+            "jq -r '.tool_input.command' | grep -q 'rm'; exit 2",
+            "jq -r '.tool_input.command' | grep -q 'rm' || echo 'No rm' >&2 && exit 2",
+            "jq -r '.tool_input.command' | grep -q 'rm' && echo 'No rm' >&2; exit 2",
+            "jq -r '.tool_input.command'; grep -q 'rm' && exit 2",
+        ] {
+            assert_eq!(recognize(command), None, "{command}");
+        }
+    }
+
+    #[test]
+    fn test_toml_string() {
+        assert_eq!(toml_string("^npm"), r#""^npm""#);
+        assert_eq!(toml_string(r"^rm\s"), r"'^rm\s'");
+        assert_eq!(toml_string(r#"say "hi""#), r#"'say "hi"'"#);
+        assert_eq!(toml_string("it's"), r#""it's""#);
+        assert_eq!(toml_string("a\"b'\\"), r#""a\"b'\\""#);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::style;
 use std::io::{self, Write};
 
 /// Hook execution output containing exit code and optional messages.
@@ -9,6 +10,9 @@ pub struct Output {
     pub stdout: Option<String>,
     /// Standard error content.
     pub stderr: Option<String>,
+    /// Whether the tool call is blocked: by exit code 2, or by a JSON
+    /// decision on stdout with exit code 0.
+    pub blocked: bool,
 }
 
 /// Creates an output that blocks the tool execution with an optional message.
@@ -17,6 +21,86 @@ pub fn block_output(message: Option<&str>) -> Output {
         exit_code: 2,
         stdout: None,
         stderr: message.map(std::string::ToString::to_string),
+        blocked: true,
+    }
+}
+
+/// Cuts `message` down to its first `limit` bytes, on a character
+/// boundary, and appends a marker with the number of bytes dropped. A
+/// message within the limit is returned unchanged.
+pub fn truncate_message(message: &str, limit: usize) -> String {
+    if message.len() <= limit {
+        return message.to_string();
+    }
+    let mut end = limit;
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = message.get(..end).unwrap_or_default();
+    format!("{head}… [truncated {} bytes]", message.len() - end)
+}
+
+/// Top-level fields common to the JSON responses of all hook events.
+#[derive(Debug, Default)]
+pub struct ResponseFields {
+    /// Hides the response from the transcript (`suppressOutput`).
+    pub suppress_output: bool,
+    /// Notice shown to the user (`systemMessage`), already expanded.
+    pub system_message: Option<String>,
+}
+
+impl ResponseFields {
+    /// Adds the fields that are set to a JSON response.
+    fn apply(&self, response: &mut serde_json::Value) {
+        let Some(map) = response.as_object_mut() else {
+            return;
+        };
+        if self.suppress_output {
+            map.insert("suppressOutput".to_string(), true.into());
+        }
+        if let Some(ref system_message) = self.system_message {
+            map.insert("systemMessage".to_string(), system_message.clone().into());
+        }
+    }
+}
+
+/// Creates an output that denies a PreToolUse tool call through Claude
+/// Code's structured protocol: a `permissionDecision = "deny"` JSON on
+/// stdout, with `reason` as the `permissionDecisionReason`, and exit code 0.
+/// `fields` adds the common top-level fields such as `suppressOutput`.
+pub fn deny_output(reason: Option<&str>, fields: &ResponseFields) -> Output {
+    let mut hook_output = serde_json::json!({
+        "hookEventName": "PreToolUse",
+        "permissionDecision": "deny",
+    });
+    if let (Some(reason), Some(map)) = (reason, hook_output.as_object_mut()) {
+        map.insert("permissionDecisionReason".to_string(), reason.into());
+    }
+    let mut response = serde_json::json!({ "hookSpecificOutput": hook_output });
+    fields.apply(&mut response);
+    Output {
+        exit_code: 0,
+        stdout: Some(response.to_string() + "\n"),
+        stderr: None,
+        blocked: true,
+    }
+}
+
+/// Creates an output that blocks after a PostToolUse tool call through
+/// Claude Code's structured protocol: a `"decision": "block"` JSON on
+/// stdout, with `reason` fed back to Claude, and exit code 0.
+/// `fields` adds the common top-level fields such as `suppressOutput`.
+pub fn post_block_output(reason: Option<&str>, fields: &ResponseFields) -> Output {
+    let mut response = serde_json::json!({ "decision": "block" });
+    if let (Some(reason), Some(map)) = (reason, response.as_object_mut()) {
+        map.insert("reason".to_string(), reason.into());
+    }
+    fields.apply(&mut response);
+    Output {
+        exit_code: 0,
+        stdout: Some(response.to_string() + "\n"),
+        stderr: None,
+        blocked: true,
     }
 }
 
@@ -26,10 +110,12 @@ pub fn no_match_output() -> Output {
         exit_code: 0,
         stdout: None,
         stderr: None,
+        blocked: false,
     }
 }
 
-/// Writes the output to stdout and stderr streams.
+/// Writes the output to stdout and stderr streams. The stderr of a block
+/// (exit code 2) is styled as an error; stdout is never styled.
 pub fn emit(output: &Output) {
     if let Some(ref stdout_content) = output.stdout {
         if let Err(e) = io::stdout().write_all(stdout_content.as_bytes()) {
@@ -41,6 +127,11 @@ pub fn emit(output: &Output) {
     }
 
     if let Some(ref stderr_content) = output.stderr {
+        let stderr_content = if output.exit_code == 2 {
+            style::error(stderr_content)
+        } else {
+            stderr_content.clone()
+        };
         if let Err(e) = io::stderr().write_all(stderr_content.as_bytes()) {
             eprintln!("Warning: failed to write to stderr: {e}");
         }
@@ -52,3 +143,30 @@ pub fn emit(output: &Output) {
         }
     }
 }
+
+/// Formats rows (the first being the header) as left-aligned columns
+/// separated by two spaces.
+pub fn table<R: AsRef<[String]>>(rows: impl IntoIterator<Item = R>) -> String {
+    let rows: Vec<R> = rows.into_iter().collect();
+    let mut widths: Vec<usize> = Vec::new();
+    for row in &rows {
+        for (i, cell) in row.as_ref().iter().enumerate() {
+            let len = cell.chars().count();
+            match widths.get_mut(i) {
+                Some(width) => *width = (*width).max(len),
+                None => widths.push(len),
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let line: Vec<String> = row
+                .as_ref()
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect();
+            line.join("  ").trim_end().to_string() + "\n"
+        })
+        .collect()
+}
@@ -4,23 +4,39 @@ use shellish_parse::multiparse;
 ///
 /// セパレータ: &&, ||, ;, |
 /// サブシェル `()` は非対応
+/// パースに失敗した場合は空白区切りの単一コマンドとして扱う
 pub fn split_compound_command(command: &str) -> Vec<Vec<String>> {
-    if command.trim().is_empty() {
-        return Vec::new();
-    }
+    try_split_compound_command(command)
+        .unwrap_or_else(|| vec![command.split_whitespace().map(|s| s.to_string()).collect()])
+}
 
-    let separators = ["&&", "||", ";", "|"];
+/// 複合コマンドのセパレータ
+const SEPARATORS: [&str; 4] = ["&&", "||", ";", "|"];
 
-    match multiparse(command, false, &separators) {
-        Ok(commands) => commands
-            .into_iter()
-            .map(|(args, _sep)| args)
-            .filter(|args| !args.is_empty())
-            .collect(),
-        Err(_) => {
-            vec![command.split_whitespace().map(|s| s.to_string()).collect()]
-        }
-    }
+/// 複合コマンドを分割する（パースに失敗した場合は None を返す）
+pub fn try_split_compound_command(command: &str) -> Option<Vec<Vec<String>>> {
+    try_split_with_separators(command)
+        .map(|commands| commands.into_iter().map(|(args, _sep)| args).collect())
+}
+
+/// 複合コマンドを分割し、各コマンドとその後ろのセパレータを返す
+/// （最後のコマンドのセパレータは None、パースに失敗した場合は None を返す）
+pub fn try_split_with_separators(
+    command: &str,
+) -> Option<Vec<(Vec<String>, Option<&'static str>)>> {
+    if command.trim().is_empty() {
+        return Some(Vec::new());
+    }
+
+    multiparse(command, false, &SEPARATORS)
+        .ok()
+        .map(|commands| {
+            commands
+                .into_iter()
+                .filter(|(args, _sep)| !args.is_empty())
+                .map(|(args, sep)| (args, sep.and_then(|i| SEPARATORS.get(i).copied())))
+                .collect()
+        })
 }
 
 /// コマンドリストを文字列として再構築（マッチング用）
@@ -164,4 +180,26 @@ mod tests {
         let result = split_compound_command("npm install 'unclosed");
         assert_eq!(result[0][0], "npm");
     }
+
+    #[test]
+    fn test_try_split_reports_parse_failure() {
+        assert!(try_split_compound_command("echo 'unclosed").is_none());
+        assert_eq!(
+            try_split_compound_command("cd x && git push"),
+            Some(vec![
+                vec!["cd".to_string(), "x".to_string()],
+                vec!["git".to_string(), "push".to_string()]
+            ])
+        );
+    }
+
+    #[test]
+    fn test_try_split_with_separators() {
+        let separators = try_split_with_separators("a | b && c || d; e")
+            .map(|commands| commands.into_iter().map(|(_, sep)| sep).collect::<Vec<_>>());
+        assert_eq!(
+            separators,
+            Some(vec![Some("|"), Some("&&"), Some("||"), Some(";"), None])
+        );
+    }
 }
@@ -0,0 +1,228 @@
+use regex_lite::Regex;
+
+/// A rule in Claude Code's permission syntax, such as `Bash(git push:*)`.
+#[derive(Debug)]
+pub struct PermissionPattern {
+    /// Tool name the rule applies to (exact match).
+    pub tool: String,
+    /// What the rule matches within that tool's input.
+    pub specifier: Specifier,
+}
+
+/// The part of a permission rule inside the parentheses.
+#[derive(Debug)]
+pub enum Specifier {
+    /// No specifier (`Bash`, `Write`): every invocation of the tool.
+    Any,
+    /// `Bash(npm test)`: the command must be exactly this.
+    Exact(String),
+    /// `Bash(git push:*)`: the command is this, or this followed by arguments.
+    WordPrefix(String),
+    /// `Bash(rm -rf*)`: the command starts with this text.
+    Prefix(String),
+    /// `Edit(src/**)`: a glob matched against the file path of other tools.
+    Path(Regex),
+}
+
+/// Parses a permission rule of the form `Tool` or `Tool(specifier)`.
+///
+/// For `Bash`, the specifier is a command, optionally ending with `:*`
+/// (prefix match on whole words) or `*` (plain prefix match). For other tools
+/// it is a glob matched against the file path. Surrounding whitespace is
+/// ignored and whitespace inside commands is normalized.
+pub fn parse_permission_pattern(pattern: &str) -> Result<PermissionPattern, String> {
+    let pattern = pattern.trim();
+    let (tool, specifier) = match pattern.split_once('(') {
+        Some((tool, rest)) => {
+            let inner = rest
+                .strip_suffix(')')
+                .ok_or_else(|| format!("'{pattern}' is missing a closing ')'"))?;
+            (tool.trim(), Some(inner.trim()))
+        }
+        None => (pattern, None),
+    };
+
+    if tool.is_empty() {
+        return Err(format!("'{pattern}' has no tool name"));
+    }
+    if !tool.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("'{tool}' is not a valid tool name"));
+    }
+
+    let specifier = match specifier {
+        None => Specifier::Any,
+        Some("") => return Err(format!("'{pattern}' has an empty specifier")),
+        Some(spec) if tool == "Bash" => parse_command_specifier(spec)?,
+        Some(spec) => {
+            let glob = spec.strip_prefix("./").unwrap_or(spec);
+            let regex = crate::glob::glob_to_regex(glob)?;
+            Specifier::Path(Regex::new(&regex).map_err(|e| e.to_string())?)
+        }
+    };
+
+    Ok(PermissionPattern {
+        tool: tool.to_string(),
+        specifier,
+    })
+}
+
+fn parse_command_specifier(spec: &str) -> Result<Specifier, String> {
+    if let Some(body) = spec.strip_suffix(":*") {
+        return Ok(Specifier::WordPrefix(command_text(body, spec)?));
+    }
+    if let Some(body) = spec.strip_suffix('*') {
+        // `rm -rf *` のように `*` 直前の空白は意味を持つため保持する
+        let mut prefix = command_text(body, spec)?;
+        if body.ends_with(char::is_whitespace) {
+            prefix.push(' ');
+        }
+        return Ok(Specifier::Prefix(prefix));
+    }
+    Ok(Specifier::Exact(command_text(spec, spec)?))
+}
+
+/// Validates and normalizes the command part of a Bash specifier.
+fn command_text(body: &str, spec: &str) -> Result<String, String> {
+    if body.contains('*') {
+        return Err(format!(
+            "'{spec}': '*' is only supported at the end of a command"
+        ));
+    }
+    let normalized = normalize_whitespace(body);
+    if normalized.is_empty() {
+        return Err(format!("'{spec}' has no command"));
+    }
+    Ok(normalized)
+}
+
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl PermissionPattern {
+    /// Returns true if a single (already split) command matches the rule.
+    pub fn matches_command(&self, command: &str) -> bool {
+        let command = normalize_whitespace(command);
+        match &self.specifier {
+            Specifier::Any => true,
+            Specifier::Exact(expected) => command == *expected,
+            Specifier::WordPrefix(prefix) => command
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(' ')),
+            Specifier::Prefix(prefix) => command.starts_with(prefix.as_str()),
+            Specifier::Path(_) => false,
+        }
+    }
+
+    /// Returns true if a file path matches the rule.
+    pub fn matches_path(&self, path: &str) -> bool {
+        match &self.specifier {
+            Specifier::Any => true,
+            Specifier::Path(glob) => glob.is_match(&path.replace('\\', "/")),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_word_prefix() {
+        let pattern = parse_permission_pattern("Bash(git push:*)").unwrap();
+        assert_eq!(pattern.tool, "Bash");
+        assert!(matches!(&pattern.specifier, Specifier::WordPrefix(p) if p == "git push"));
+    }
+
+    #[test]
+    fn test_parse_plain_prefix() {
+        let pattern = parse_permission_pattern("Bash(rm -rf*)").unwrap();
+        assert!(matches!(&pattern.specifier, Specifier::Prefix(p) if p == "rm -rf"));
+
+        let pattern = parse_permission_pattern("Bash(rm -rf *)").unwrap();
+        assert!(matches!(&pattern.specifier, Specifier::Prefix(p) if p == "rm -rf "));
+    }
+
+    #[test]
+    fn test_parse_exact() {
+        let pattern = parse_permission_pattern("Bash(npm run test)").unwrap();
+        assert!(matches!(&pattern.specifier, Specifier::Exact(c) if c == "npm run test"));
+    }
+
+    #[test]
+    fn test_parse_with_spaces() {
+        let pattern = parse_permission_pattern("  Bash ( git   push :* )  ").unwrap();
+        assert_eq!(pattern.tool, "Bash");
+        assert!(matches!(&pattern.specifier, Specifier::WordPrefix(p) if p == "git push"));
+    }
+
+    #[test]
+    fn test_parse_tool_only() {
+        let pattern = parse_permission_pattern("WebFetch").unwrap();
+        assert_eq!(pattern.tool, "WebFetch");
+        assert!(matches!(pattern.specifier, Specifier::Any));
+
+        let pattern = parse_permission_pattern("mcp__github__create_issue").unwrap();
+        assert_eq!(pattern.tool, "mcp__github__create_issue");
+    }
+
+    #[test]
+    fn test_parse_non_bash_tool() {
+        let pattern = parse_permission_pattern("Edit(src/**)").unwrap();
+        assert_eq!(pattern.tool, "Edit");
+        assert!(pattern.matches_path("/work/app/src/lib/mod.rs"));
+        assert!(!pattern.matches_path("/work/app/tests/it.rs"));
+        assert!(!pattern.matches_command("src/lib.rs"));
+
+        let pattern = parse_permission_pattern("Read(./.env)").unwrap();
+        assert!(pattern.matches_path("/work/app/.env"));
+        assert!(!pattern.matches_path("/work/app/.env.example"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_permission_pattern("Bash(git push:*").is_err());
+        assert!(parse_permission_pattern("(git push)").is_err());
+        assert!(parse_permission_pattern("Bash()").is_err());
+        assert!(parse_permission_pattern("Bash(:*)").is_err());
+        assert!(parse_permission_pattern("Bash(git * main)").is_err());
+        assert!(parse_permission_pattern("Ba sh(ls)").is_err());
+        assert!(parse_permission_pattern("").is_err());
+    }
+
+    #[test]
+    fn test_matches_word_prefix() {
+        let pattern = parse_permission_pattern("Bash(git push:*)").unwrap();
+        assert!(pattern.matches_command("git push"));
+        assert!(pattern.matches_command("git push origin main"));
+        assert!(pattern.matches_command("git   push  --force"));
+        assert!(!pattern.matches_command("git pushx"));
+        assert!(!pattern.matches_command("git pull"));
+        assert!(!pattern.matches_command("echo git push"));
+    }
+
+    #[test]
+    fn test_matches_plain_prefix() {
+        let pattern = parse_permission_pattern("Bash(rm -rf*)").unwrap();
+        assert!(pattern.matches_command("rm -rf /tmp/x"));
+        assert!(pattern.matches_command("rm -rfv build"));
+        assert!(!pattern.matches_command("rm -r build"));
+    }
+
+    #[test]
+    fn test_matches_exact() {
+        let pattern = parse_permission_pattern("Bash(npm run test)").unwrap();
+        assert!(pattern.matches_command("npm run test"));
+        assert!(pattern.matches_command("npm  run test"));
+        assert!(!pattern.matches_command("npm run test --watch"));
+    }
+
+    #[test]
+    fn test_matches_any() {
+        let pattern = parse_permission_pattern("Bash").unwrap();
+        assert!(pattern.matches_command("anything at all"));
+        assert!(pattern.matches_path("/any/path"));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::batch::Decision;
+use crate::config::Config;
+use crate::error::Result;
+use crate::output::Output;
+use crate::parse_input;
+use crate::rule::{EventType, HookInput, Rule};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Re-evaluates every entry of an audit log written by `--report-file`
+/// against the current rules and prints the entries whose decision (block
+/// or allow) changed, followed by a summary.
+///
+/// No action is executed. Lines that are not report entries, or that were
+/// written without the original input, are listed as unreadable. The exit
+/// code is 1 if `fail_on_change` is set and any decision changed.
+pub fn run_replay(
+    audit_log: &Path,
+    config: &Config,
+    rules: &[Rule],
+    fail_on_change: bool,
+) -> Result<Output> {
+    let content = fs::read_to_string(audit_log)?;
+    let mut stdout = String::new();
+    let (mut changed, mut unchanged, mut unreadable) = (0, 0, 0);
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = index + 1;
+        let (event, input, was_blocked, before) = match parse_entry(line) {
+            Ok(entry) => entry,
+            Err(e) => {
+                unreadable += 1;
+                stdout.push_str(&format!("unreadable  line {line_number}: {e}\n"));
+                continue;
+            }
+        };
+
+        let decision = Decision::evaluate(&event, &input, config, rules);
+        if decision.blocked() == was_blocked {
+            unchanged += 1;
+            continue;
+        }
+        changed += 1;
+        let rule = decision.rule.as_ref().map(|(rule, _)| rule.as_str());
+        let after = describe(decision.blocked(), rule);
+        stdout.push_str(&format!(
+            "changed     line {line_number}: {} {}{}\n  - {before}\n  + {after}\n",
+            event.as_str(),
+            input.tool_name,
+            subject(&input)
+        ));
+    }
+
+    if changed > 0 || unreadable > 0 {
+        stdout.push('\n');
+    }
+    stdout.push_str(&format!(
+        "{} entries: {changed} changed, {unchanged} unchanged, {unreadable} unreadable\n",
+        changed + unchanged + unreadable
+    ));
+    Ok(Output {
+        exit_code: i32::from(fail_on_change && changed > 0),
+        stdout: Some(stdout),
+        stderr: None,
+        blocked: false,
+    })
+}
+
+/// Reads the event, the original hook input, and the recorded decision
+/// (whether it blocked, and its description) from a report line.
+fn parse_entry(line: &str) -> std::result::Result<(EventType, HookInput, bool, String), String> {
+    let entry: Value = serde_json::from_str(line).map_err(|e| format!("invalid JSON: {e}"))?;
+    let event = entry
+        .get("event")
+        .and_then(Value::as_str)
+        .ok_or("missing 'event'")?;
+    let event = EventType::from_str(event).map_err(|e| e.to_string())?;
+    let input = match entry.get("input") {
+        Some(input) if input.is_object() => input.clone(),
+        _ => return Err("missing 'input' (not written by --report-file?)".to_string()),
+    };
+    let input = parse_input(input).map_err(|e| format!("invalid input: {e}"))?;
+    // decision がない古いレポートは exit code から判定する
+    let blocked = match entry.get("decision").and_then(Value::as_str) {
+        Some(decision) => decision == "block",
+        None => {
+            entry
+                .get("exit_code")
+                .and_then(Value::as_i64)
+                .ok_or("missing 'exit_code'")?
+                == 2
+        }
+    };
+    let rule = entry.get("matched_rule").and_then(Value::as_str);
+    Ok((event, input, blocked, describe(blocked, rule)))
+}
+
+fn describe(blocked: bool, rule: Option<&str>) -> String {
+    match rule {
+        Some(rule) if blocked => format!("block by rule '{rule}'"),
+        _ if blocked => "block".to_string(),
+        _ => "allow".to_string(),
+    }
+}
+
+/// The command or file path of the input, for identifying the entry.
+fn subject(input: &HookInput) -> String {
+    input
+        .tool_input
+        .command
+        .as_ref()
+        .or(input.tool_input.file_path.as_ref())
+        .map(|subject| format!(": {subject}"))
+        .unwrap_or_default()
+}
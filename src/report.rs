@@ -0,0 +1,135 @@
+use crate::action;
+use crate::config::Config;
+use crate::output::Output;
+use crate::rule::{EvaluationResult, EventType, HookInput, Rule, RuleTrace};
+use crate::style;
+use chrono::Local;
+use serde_json::{Value, json};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+/// Version of the report schema. Bumped whenever a field is removed or its
+/// meaning changes; new fields may be added without a bump.
+pub const REPORT_VERSION: u32 = 1;
+
+/// The `--report json` report of a hook invocation.
+pub struct Report(Value);
+
+impl Report {
+    /// Records the evaluation. `raw_input` is the hook input as received,
+    /// and `trace` has one entry per rule in evaluation order (see
+    /// `rule::trace_rules`).
+    pub fn new(
+        config: &Config,
+        event: &EventType,
+        input: &HookInput,
+        raw_input: Option<&Value>,
+        rules: &[Rule],
+        trace: &[(String, RuleTrace)],
+        result: Option<&EvaluationResult>,
+    ) -> Self {
+        let rules: Vec<Value> = rules
+            .iter()
+            .zip(trace)
+            .map(|(rule, (name, outcome))| {
+                let (outcome, failed_conditions) = match outcome {
+                    RuleTrace::WrongEvent => ("wrong_event", &[][..]),
+                    RuleTrace::MatcherMismatch => ("matcher_mismatch", &[][..]),
+                    RuleTrace::ConditionsFailed(reasons) => ("conditions_failed", &reasons[..]),
+                    RuleTrace::Matched => ("matched", &[][..]),
+                    RuleTrace::Bypassed(_) => ("bypassed", &[][..]),
+                    RuleTrace::NotReached => ("not_reached", &[][..]),
+                };
+                json!({
+                    "name": name,
+                    "event": rule.event.as_str(),
+                    "action": rule.action.as_str(),
+                    "outcome": outcome,
+                    "failed_conditions": failed_conditions,
+                })
+            })
+            .collect();
+
+        let (matched_rule, actions, dry_run_matches) = match result {
+            Some(result) => (
+                result
+                    .terminal_result
+                    .as_ref()
+                    .map(|matched| matched.rule_name.clone()),
+                result
+                    .log_results
+                    .iter()
+                    .chain(&result.terminal_result)
+                    .map(|matched| action::action_json(matched, &result.context))
+                    .collect(),
+                result
+                    .shadow_results
+                    .iter()
+                    .map(|matched| action::action_json(matched, &result.context))
+                    .collect(),
+            ),
+            None => (None, Vec::new(), Vec::new()),
+        };
+
+        Report(json!({
+            "report_version": REPORT_VERSION,
+            "timestamp": Local::now().format("%Y-%m-%dT%H:%M:%S%:z").to_string(),
+            "config_files": config.source_files,
+            "event": event.as_str(),
+            "tool": input.tool_name,
+            "input": raw_input,
+            "rules": rules,
+            "matched_rule": matched_rule,
+            "actions": actions,
+            "dry_run_matches": dry_run_matches,
+        }))
+    }
+
+    /// Completes the report with the hook's outcome and writes it as a
+    /// single line: appended to `path` if given, or after the hook's own
+    /// stderr output otherwise. stdout is never touched.
+    ///
+    /// The decision is taken from `out.blocked`, as the exit code does not
+    /// show a block reported as a JSON decision.
+    ///
+    /// A report file that cannot be written only produces a warning, so the
+    /// hook's decision is unaffected.
+    pub fn emit(self, out: &mut Output, dry_run: bool, elapsed: Duration, path: Option<&Path>) {
+        let Report(mut report) = self;
+        if let Some(map) = report.as_object_mut() {
+            map.insert("dry_run".to_string(), json!(dry_run));
+            let decision = if out.blocked { "block" } else { "allow" };
+            map.insert("decision".to_string(), json!(decision));
+            map.insert("exit_code".to_string(), json!(out.exit_code));
+            map.insert(
+                "duration_ms".to_string(),
+                json!(elapsed.as_secs_f64() * 1000.0),
+            );
+        }
+        let line = report.to_string();
+        let Some(path) = path else {
+            out.stderr = Some(match out.stderr.take() {
+                Some(stderr) => format!("{stderr}\n{line}"),
+                None => line,
+            });
+            return;
+        };
+
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{line}"));
+        if let Err(e) = written {
+            eprintln!(
+                "{}",
+                style::warning(&format!(
+                    "Warning: failed to write report to {}: {e}",
+                    path.display()
+                ))
+            );
+        }
+    }
+}
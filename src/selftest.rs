@@ -0,0 +1,266 @@
+use crate::config::{Config, ConfigTest};
+use crate::error::CchookedError;
+use crate::output::{self, Output};
+use crate::rule::{self, ActionType, EventType, HookInput, Rule};
+use crate::{build_context, parse_input};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Runs the config's `[[tests]]` cases and reports the result of each.
+///
+/// Actions are not executed: a test only inspects which rules matched, so
+/// run and log actions have no side effects. Exits with 1 if any test fails.
+pub fn run_tests(config: &Config, rules: &[Rule]) -> Output {
+    let mut report = Vec::new();
+    let mut failed = 0;
+
+    for test in &config.tests {
+        match check(test, config, rules) {
+            Ok(()) => report.push(format!("PASS  {}", test.name)),
+            Err(reason) => {
+                failed += 1;
+                report.push(format!("FAIL  {}: {reason}", test.name));
+            }
+        }
+    }
+
+    let total = config.tests.len();
+    let plural = if total == 1 { "" } else { "s" };
+    report.push(format!(
+        "{total} test{plural}, {} passed, {failed} failed",
+        total - failed
+    ));
+    Output {
+        exit_code: if failed > 0 { 1 } else { 0 },
+        stdout: Some(report.join("\n") + "\n"),
+        stderr: None,
+        blocked: false,
+    }
+}
+
+/// Evaluates one test case, returning why it failed.
+fn check(test: &ConfigTest, config: &Config, rules: &[Rule]) -> Result<(), String> {
+    let event = EventType::from_str(&test.event).map_err(|e| e.to_string())?;
+    let input = parse_input(test.input.clone()).map_err(|e| e.to_string())?;
+    let outcome = Outcome::evaluate(&event, &input, config, rules);
+    outcome.check(&test.expect)
+}
+
+/// The rules that matched an input, without any action executed.
+struct Outcome {
+    /// Matched rules in evaluation order, ending with the terminal rule if any.
+    matched: Vec<(String, ActionType)>,
+    /// Whether the terminal rule is a block rule.
+    blocked: bool,
+}
+
+impl Outcome {
+    fn evaluate(event: &EventType, input: &HookInput, config: &Config, rules: &[Rule]) -> Self {
+        let context = build_context(input, config);
+        let result = rule::evaluate_rules(rules, event, input, context);
+        let blocked = result
+            .as_ref()
+            .and_then(|result| result.terminal_result.as_ref())
+            .is_some_and(|terminal| terminal.action == ActionType::Block);
+        let matched = result
+            .iter()
+            .flat_map(|result| result.log_results.iter().chain(&result.terminal_result))
+            .map(|matched| (matched.rule_name.clone(), matched.action.clone()))
+            .collect();
+        Self { matched, blocked }
+    }
+
+    /// Returns the rule that decided the outcome: the terminal rule, or the
+    /// first log rule if evaluation ran to the end.
+    fn deciding_rule(&self) -> Option<&(String, ActionType)> {
+        self.matched
+            .iter()
+            .find(|(_, action)| *action != ActionType::Log)
+            .or_else(|| self.matched.first())
+    }
+
+    fn decision(&self) -> &'static str {
+        if self.blocked { "block" } else { "allow" }
+    }
+
+    fn describe(&self) -> String {
+        match self.deciding_rule() {
+            Some((name, _)) if self.blocked => format!("block by rule '{name}'"),
+            _ => "allow".to_string(),
+        }
+    }
+
+    /// Checks an expectation (`block`, `allow`, or `rule:<name>`), returning
+    /// why it was not met.
+    fn check(&self, expect: &str) -> Result<(), String> {
+        let matched: Vec<&str> = self.matched.iter().map(|(name, _)| name.as_str()).collect();
+        match expect.split_once(':') {
+            None if expect == "block" => match self.blocked {
+                true => Ok(()),
+                false => Err(format!("expected block, got {}", self.describe())),
+            },
+            None if expect == "allow" => match self.blocked {
+                false => Ok(()),
+                true => Err(format!("expected allow, got {}", self.describe())),
+            },
+            Some(("rule", name)) => {
+                if matched.contains(&name) {
+                    Ok(())
+                } else if matched.is_empty() {
+                    Err(format!(
+                        "expected rule '{name}' to match, but no rule matched"
+                    ))
+                } else {
+                    Err(format!(
+                        "expected rule '{name}' to match, got {}",
+                        matched.join(", ")
+                    ))
+                }
+            }
+            Some(("transform", _)) => Err(
+                "transform expectations are not supported (no rule rewrites commands)".to_string(),
+            ),
+            _ => Err(format!(
+                "unknown expectation '{expect}' (expected block, allow, rule:<name>, or transform:<command>)"
+            )),
+        }
+    }
+}
+
+/// Replays every `*.json` hook input in `dir` against the rules and prints
+/// a table of what each one matched and the resulting decision.
+///
+/// Actions are not executed. If `expect_file` is given, its top-level
+/// `"<fixture file name>" = "<expectation>"` entries are checked as in
+/// `[[tests]]`, and the exit code is 1 if any of them is not met. Fixtures
+/// that are not valid hook input also make the exit code 1.
+pub fn run_fixtures(
+    event: &EventType,
+    dir: &Path,
+    expect_file: Option<&Path>,
+    config: &Config,
+    rules: &[Rule],
+) -> crate::error::Result<Output> {
+    let mut expectations = match expect_file {
+        Some(path) => load_expectations(path)?,
+        None => BTreeMap::new(),
+    };
+
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json") && path.is_file())
+        .collect();
+    fixtures.sort();
+
+    let mut header = ["FIXTURE", "RULE", "ACTION", "DECISION"]
+        .map(str::to_string)
+        .to_vec();
+    if expect_file.is_some() {
+        header.push("RESULT".to_string());
+    }
+    let mut rows = vec![header];
+    let mut failed = 0;
+    let mut checked = 0;
+
+    for path in &fixtures {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let expect = expectations.remove(&name);
+        let input = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+            .and_then(|value| parse_input(value).map_err(|e| e.to_string()));
+        let mut row = match &input {
+            Ok(input) => {
+                let outcome = Outcome::evaluate(event, input, config, rules);
+                let (rule, action) = match outcome.deciding_rule() {
+                    Some((rule, action)) => (rule.clone(), action.as_str().to_string()),
+                    None => ("(none)".to_string(), "-".to_string()),
+                };
+                let mut row = vec![name, rule, action, outcome.decision().to_string()];
+                if let Some(expect) = &expect {
+                    checked += 1;
+                    match outcome.check(expect) {
+                        Ok(()) => row.push("PASS".to_string()),
+                        Err(reason) => {
+                            failed += 1;
+                            row.push(format!("FAIL: {reason}"));
+                        }
+                    }
+                }
+                row
+            }
+            Err(e) => {
+                failed += 1;
+                vec![
+                    name,
+                    "-".to_string(),
+                    "-".to_string(),
+                    format!("invalid input: {e}"),
+                ]
+            }
+        };
+        if expect_file.is_some() && row.len() == 4 {
+            row.push("-".to_string());
+        }
+        rows.push(row);
+    }
+
+    let mut stdout = output::table(rows);
+    for name in expectations.keys() {
+        checked += 1;
+        failed += 1;
+        stdout.push_str(&format!("FAIL  {name}: no such fixture\n"));
+    }
+    if expect_file.is_some() {
+        let plural = if checked == 1 { "" } else { "s" };
+        stdout.push_str(&format!(
+            "{checked} expectation{plural}, {} passed, {failed} failed\n",
+            checked - failed
+        ));
+    }
+    Ok(Output {
+        exit_code: if failed > 0 { 1 } else { 0 },
+        stdout: Some(stdout),
+        stderr: None,
+        blocked: false,
+    })
+}
+
+/// Reads an expectations file mapping fixture file names to expectations.
+fn load_expectations(path: &Path) -> crate::error::Result<BTreeMap<String, String>> {
+    let content = fs::read_to_string(path)?;
+    toml::from_str(&content).map_err(|e| CchookedError::ConfigParseError {
+        path: path.display().to_string(),
+        detail: e.to_string(),
+    })
+}
+
+/// Checks every rule's `examples` and reports the ones that fail.
+///
+/// Exits with 1 if any example fails.
+pub fn run_examples(rules: &[Rule]) -> Output {
+    let results: Vec<Result<(), String>> = rules.iter().flat_map(rule::check_examples).collect();
+    let mut report: Vec<String> = results
+        .iter()
+        .filter_map(|result| result.as_ref().err())
+        .map(|reason| format!("FAIL  {reason}"))
+        .collect();
+    let failed = report.len();
+
+    let total = results.len();
+    let plural = if total == 1 { "" } else { "s" };
+    report.push(format!(
+        "{total} example{plural}, {} passed, {failed} failed",
+        total - failed
+    ));
+    Output {
+        exit_code: if failed > 0 { 1 } else { 0 },
+        stdout: Some(report.join("\n") + "\n"),
+        stderr: None,
+        blocked: false,
+    }
+}
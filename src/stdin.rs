@@ -0,0 +1,60 @@
+use crate::error::{CchookedError, Result};
+use std::io::{self, Read};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for stdin when `--stdin-timeout-ms` is not given.
+pub const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+/// Reads all of stdin, failing if no data arrives for `timeout_ms`
+/// milliseconds (0 waits forever).
+///
+/// The timeout only covers silence: it restarts with every chunk received,
+/// so a large input that arrives slowly is still read completely.
+pub fn read_to_string(timeout_ms: u64) -> Result<String> {
+    if timeout_ms == 0 {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        return Ok(buffer);
+    }
+
+    // 読み込みスレッドはタイムアウト後も stdin でブロックしたまま残るが、
+    // プロセスの終了とともに破棄される
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        let mut chunk = [0u8; 8192];
+        loop {
+            let data = match stdin.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => Ok(chunk.get(..n).unwrap_or_default().to_vec()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = data.is_err();
+            if sender.send(data).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let timeout = Duration::from_millis(timeout_ms);
+    let mut buffer = Vec::new();
+    loop {
+        match receiver.recv_timeout(timeout) {
+            Ok(data) => buffer.extend(data?),
+            Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(CchookedError::StdinTimeout { timeout_ms });
+            }
+        }
+    }
+    String::from_utf8(buffer).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        )
+        .into()
+    })
+}
@@ -0,0 +1,71 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Environment variable that turns colors off in auto mode (see
+/// https://no-color.org).
+const NO_COLOR_ENV: &str = "NO_COLOR";
+
+const AUTO: u8 = 0;
+const ALWAYS: u8 = 1;
+const NEVER: u8 = 2;
+
+/// `[settings] color`; auto until a config has been loaded.
+static CHOICE: AtomicU8 = AtomicU8::new(AUTO);
+
+/// Sets when stderr is colored from `[settings] color` ("auto", "always"
+/// or "never"; unset means auto).
+pub fn set_choice(color: Option<&str>) {
+    let choice = match color {
+        Some("always") => ALWAYS,
+        Some("never") => NEVER,
+        _ => AUTO,
+    };
+    CHOICE.store(choice, Ordering::SeqCst);
+}
+
+/// Whether stderr is colored: always or never as configured, otherwise
+/// only when stderr is a terminal and `NO_COLOR` is not set.
+fn enabled() -> bool {
+    match CHOICE.load(Ordering::SeqCst) {
+        ALWAYS => true,
+        NEVER => false,
+        _ => {
+            std::env::var_os(NO_COLOR_ENV).is_none_or(|value| value.is_empty())
+                && std::io::stderr().is_terminal()
+        }
+    }
+}
+
+/// Wraps each line of `text` in the SGR sequence `code`, so that every
+/// line stays styled and ends reset. JSON lines are left plain so that
+/// records such as the `--report` output stay machine-readable.
+fn paint(code: &str, text: &str) -> String {
+    if !enabled() {
+        return text.to_string();
+    }
+    text.split('\n')
+        .map(|line| {
+            if line.is_empty() || line.starts_with('{') {
+                line.to_string()
+            } else {
+                format!("\x1b[{code}m{line}\x1b[0m")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Styles a block message or an error (bold red).
+pub fn error(text: &str) -> String {
+    paint("1;31", text)
+}
+
+/// Styles a warning (yellow).
+pub fn warning(text: &str) -> String {
+    paint("33", text)
+}
+
+/// Styles diagnostic output such as `--verbose` (dimmed).
+pub fn dim(text: &str) -> String {
+    paint("2", text)
+}
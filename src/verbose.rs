@@ -0,0 +1,165 @@
+use crate::action;
+use crate::config::Config;
+use crate::explain;
+use crate::rule::{self, EvaluationResult, EventType, HookInput, Rule, RuleTrace};
+use crate::style;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Environment variable that turns on verbose output like `--verbose`.
+pub const DEBUG_ENV: &str = "CCHOOKED_DEBUG";
+
+/// Prefix of every diagnostic line, so they cannot be mistaken for block
+/// messages on stderr.
+const PREFIX: &str = "[cchooked]";
+
+/// Diagnostics printed to stderr with `--verbose` or `CCHOOKED_DEBUG`.
+///
+/// Every method does nothing when verbose output is off, so callers do not
+/// need to check. Nothing is ever written to stdout.
+pub struct Verbose {
+    enabled: bool,
+    phase_start: Instant,
+}
+
+impl Verbose {
+    /// Enables verbose output if `flag` is set or `CCHOOKED_DEBUG` is set to
+    /// anything other than an empty string or "0".
+    pub fn new(flag: bool) -> Self {
+        let from_env =
+            std::env::var(DEBUG_ENV).is_ok_and(|value| !value.is_empty() && value != "0");
+        Verbose {
+            enabled: flag || from_env,
+            phase_start: Instant::now(),
+        }
+    }
+
+    /// Returns true if diagnostics are printed.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Prints `message`, prefixing each of its lines.
+    pub fn log(&self, message: &str) {
+        if !self.enabled {
+            return;
+        }
+        for line in message.lines() {
+            eprintln!("{}", style::dim(&format!("{PREFIX} {line}")));
+        }
+    }
+
+    /// Prints `message` as a warning.
+    pub fn warning(&self, message: &str) {
+        if !self.enabled {
+            return;
+        }
+        eprintln!(
+            "{}",
+            style::warning(&format!("{PREFIX} Warning: {message}"))
+        );
+    }
+
+    /// Prints how long the phase that just finished took, and starts timing
+    /// the next one.
+    pub fn phase(&mut self, name: &str) {
+        let elapsed = self.phase_start.elapsed();
+        self.log(&format!(
+            "{name} took {:.3}ms",
+            elapsed.as_secs_f64() * 1000.0
+        ));
+        self.phase_start = Instant::now();
+    }
+
+    /// Prints the config files that were read, how many rules were compiled
+    /// for each event, and the rules' warnings.
+    pub fn config(&self, config: &Config, rules: &[Rule]) {
+        if !self.enabled {
+            return;
+        }
+        if config.source_files.is_empty() {
+            self.log("config: no config file found");
+        }
+        for path in &config.source_files {
+            self.log(&format!("config: {}", path.display()));
+        }
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for rule in rules {
+            *counts.entry(rule.event.as_str()).or_default() += 1;
+        }
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(event, count)| format!("{event} {count}"))
+            .collect();
+        self.log(&format!(
+            "compiled {} rule(s){}{}",
+            rules.len(),
+            if counts.is_empty() { "" } else { ": " },
+            counts.join(", ")
+        ));
+        for warning in &config.warnings {
+            self.warning(warning);
+        }
+        for rule in rules {
+            for warning in &rule.warnings {
+                self.warning(&format!("rule '{}' {warning}", rule.name));
+            }
+        }
+        for (first, second) in rule::priority_ties(rules, config) {
+            self.warning(&format!(
+                "rules '{}' and '{}' have the same priority, event and matcher; '{}' is evaluated first",
+                first.name, second.name, first.name
+            ));
+        }
+    }
+
+    /// Prints why each rule was skipped or matched, then the expanded
+    /// parameters of every action that will run.
+    pub fn evaluation(
+        &self,
+        event: &EventType,
+        input: &HookInput,
+        rules: &[Rule],
+        trace: &[(String, RuleTrace)],
+        result: Option<&EvaluationResult>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.log(&format!(
+            "event: {}, tool: {}",
+            event.as_str(),
+            input.tool_name
+        ));
+        if let Some(command) = &input.tool_input.command
+            && rules.iter().any(|rule| rule.when.uses_parsed())
+            && crate::parser::try_split_compound_command(command).is_none()
+        {
+            self.warning("could not parse the command for when.parsed, treating it as no match");
+        }
+        for line in explain::trace_lines(rules, trace, input) {
+            self.log(&line);
+        }
+        let Some(result) = result else {
+            self.log("no rule matched");
+            return;
+        };
+        for shadow in &result.shadow_results {
+            self.log(&format!(
+                "dry_run rule '{}' matched; its {} action is only recorded",
+                shadow.rule_name,
+                shadow.action.as_str()
+            ));
+        }
+        for matched in result.log_results.iter().chain(&result.terminal_result) {
+            self.log(&format!(
+                "action {} by rule '{}'",
+                matched.action.as_str(),
+                matched.rule_name
+            ));
+            for line in action::describe_action(matched, &result.context) {
+                self.log(&format!("  {line}"));
+            }
+        }
+    }
+}
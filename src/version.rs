@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+
+/// A semantic version (`MAJOR.MINOR.PATCH[-PRERELEASE][+BUILD]`).
+///
+/// Build metadata is accepted but ignored when comparing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: Vec<String>,
+}
+
+impl Version {
+    /// Parses a version string, returning a description of the problem on failure.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid version '{s}' (expected MAJOR.MINOR.PATCH)");
+        let without_build = s.split_once('+').map_or(s, |(version, _)| version);
+        let (core, pre) = match without_build.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (without_build, None),
+        };
+
+        let numbers: Vec<u64> = core
+            .split('.')
+            .map(|part| {
+                if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                part.parse().map_err(|_| invalid())
+            })
+            .collect::<Result<_, _>>()?;
+        let [major, minor, patch] = *numbers.as_slice() else {
+            return Err(invalid());
+        };
+
+        let pre = match pre {
+            Some(pre) => {
+                let identifiers: Vec<String> = pre.split('.').map(str::to_string).collect();
+                if identifiers.iter().any(|id| {
+                    id.is_empty() || !id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                }) {
+                    return Err(invalid());
+                }
+                identifiers
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                // プレリリース版は同じバージョンの正式版より前
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre(&self.pre, &other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares pre-release identifiers: numeric identifiers numerically and
+/// below alphanumeric ones, a longer list after its prefix.
+fn compare_pre(a: &[String], b: &[String]) -> Ordering {
+    for (x, y) in a.iter().zip(b) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_compare_numbers() {
+        assert!(v("0.4.0") > v("0.3.1"));
+        assert!(v("0.10.0") > v("0.9.9"));
+        assert!(v("1.0.0") > v("0.99.99"));
+        assert_eq!(v("1.2.3"), v("1.2.3+build.5"));
+    }
+
+    #[test]
+    fn test_compare_prerelease() {
+        assert!(v("1.0.0-alpha") < v("1.0.0"));
+        assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.1") < v("1.0.0-alpha.beta"));
+        assert!(v("1.0.0-beta.2") < v("1.0.0-beta.11"));
+        assert!(v("1.0.0-rc.1") > v("1.0.0-beta.11"));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for s in [
+            "", "1", "1.2", "1.2.3.4", "1.x.0", "v1.2.3", "1.2.3-", "1..3",
+        ] {
+            assert!(Version::parse(s).is_err(), "{s}");
+        }
+    }
+}
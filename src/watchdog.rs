@@ -0,0 +1,95 @@
+use crate::style;
+use std::process::Command;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable that sets the time limit like `[settings]
+/// max_runtime_ms`, taking precedence over it.
+pub const MAX_RUNTIME_ENV: &str = "CCHOOKED_MAX_RUNTIME_MS";
+
+/// Whether the tool call is blocked when the time limit is exceeded.
+/// Allowing is the default, so a hung hook cannot lock up a session.
+static BLOCK_ON_TIMEOUT: AtomicBool = AtomicBool::new(false);
+
+/// Process group of the run action currently executing, if any.
+static ACTIVE_CHILD: Mutex<Option<u32>> = Mutex::new(None);
+
+/// Set once the time limit has passed and the watchdog is ending the process.
+static TIMED_OUT: AtomicBool = AtomicBool::new(false);
+
+/// Reads the time limit from `CCHOOKED_MAX_RUNTIME_MS`, warning about and
+/// ignoring a value that is not a number of milliseconds.
+pub fn max_runtime_from_env() -> Option<u64> {
+    let value = std::env::var(MAX_RUNTIME_ENV).ok()?;
+    match value.parse() {
+        Ok(ms) => Some(ms),
+        Err(_) => {
+            eprintln!(
+                "{}",
+                style::warning(&format!(
+                    "Warning: ignoring {MAX_RUNTIME_ENV}={value:?}: expected milliseconds"
+                ))
+            );
+            None
+        }
+    }
+}
+
+/// Starts a thread that ends the process once `max_runtime_ms` have passed
+/// since `started`, killing the run action in progress and exiting with 0
+/// or 2 according to the timeout policy. 0 means no limit.
+pub fn start(started: Instant, max_runtime_ms: u64) {
+    if max_runtime_ms == 0 {
+        return;
+    }
+    let deadline = started + Duration::from_millis(max_runtime_ms);
+    thread::spawn(move || {
+        thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        TIMED_OUT.store(true, Ordering::SeqCst);
+        kill_active_child();
+        let block = BLOCK_ON_TIMEOUT.load(Ordering::SeqCst);
+        let policy = if block {
+            "failing closed"
+        } else {
+            "failing open"
+        };
+        eprintln!("cchooked exceeded {max_runtime_ms}ms — {policy}");
+        std::process::exit(if block { 2 } else { 0 });
+    });
+}
+
+/// Sets the timeout policy from `[settings] timeout_policy`.
+pub fn set_policy(policy: Option<&str>) {
+    BLOCK_ON_TIMEOUT.store(policy == Some("block"), Ordering::SeqCst);
+}
+
+/// Records the run action child (started in its own process group) so it
+/// can be killed on timeout; `None` once it has finished.
+pub fn track_child(pid: Option<u32>) {
+    if let Ok(mut child) = ACTIVE_CHILD.lock() {
+        *child = pid;
+    }
+}
+
+/// Blocks forever once the time limit has passed, leaving the watchdog to
+/// end the process. Called after a run action finishes, so that a child
+/// killed on timeout does not let the hook carry on with its own decision.
+pub fn wait_if_timed_out() {
+    if TIMED_OUT.load(Ordering::SeqCst) {
+        loop {
+            thread::park();
+        }
+    }
+}
+
+fn kill_active_child() {
+    let Some(pid) = ACTIVE_CHILD.lock().ok().and_then(|child| *child) else {
+        return;
+    };
+    // プロセスグループごと終了させ、sh -c が起動した子孫も残さない
+    let _ = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{pid}")])
+        .status();
+}